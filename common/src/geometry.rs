@@ -575,6 +575,41 @@ impl Rect {
         let y1 = self.y1.min(other.y1);
         Rect::new(x0, y0, x1.max(x0), y1.max(y0))
     }
+
+    /// Returns `true` if the point `(x, y)` lies within `self`.
+    /// See [`Rect::contains`].
+    #[inline]
+    pub fn contains_point(&self, x: f64, y: f64) -> bool {
+        self.contains(Point::new(x, y))
+    }
+
+    /// The intersection of two rectangles, or `None` if they don't overlap
+    /// with a positive area.
+    ///
+    /// Rectangles that merely share an edge don't overlap, nor do
+    /// rectangles with negative width or height. If any coordinate
+    /// is NaN, the result is `None`.
+    #[inline]
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if self.is_nan() || other.is_nan() {
+            return None;
+        }
+        let result = self.intersect(*other);
+        (result.width() > 0.0 && result.height() > 0.0).then_some(result)
+    }
+
+    /// Returns `true` if the two rectangles overlap with a positive area.
+    /// See [`Rect::intersection`].
+    #[inline]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Is any coordinate of this rectangle NaN?
+    #[inline]
+    pub fn is_nan(&self) -> bool {
+        self.x0.is_nan() || self.y0.is_nan() || self.x1.is_nan() || self.y1.is_nan()
+    }
 }
 
 /// A 2D size. Derived from [kurbo](https://github.com/linebender/kurbo).
@@ -878,3 +913,71 @@ impl Neg for Vec2 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Point, Rect};
+
+    #[test]
+    fn rect_contains_point() {
+        let rect = Rect::new(10.0, 10.0, 20.0, 30.0);
+        assert!(rect.contains_point(10.0, 10.0));
+        assert!(rect.contains_point(15.0, 29.9));
+        // The maximum edges are exclusive.
+        assert!(!rect.contains_point(20.0, 15.0));
+        assert!(!rect.contains_point(15.0, 30.0));
+        assert!(!rect.contains_point(f64::NAN, 15.0));
+        assert!(!Rect::new(10.0, 10.0, 10.0, 10.0).contains_point(10.0, 10.0));
+        assert!(!Rect::new(20.0, 10.0, 10.0, 30.0).contains_point(15.0, 15.0));
+    }
+
+    #[test]
+    fn rect_area() {
+        assert_eq!(200.0, Rect::new(10.0, 10.0, 20.0, 30.0).area());
+        assert_eq!(0.0, Rect::new(10.0, 10.0, 10.0, 30.0).area());
+        assert!(Rect::new(10.0, 10.0, 10.0, 30.0).is_empty());
+        // A rectangle with negative width has negative area.
+        assert_eq!(-200.0, Rect::new(20.0, 10.0, 10.0, 30.0).area());
+        assert_eq!(200.0, Rect::new(20.0, 10.0, 10.0, 30.0).abs().area());
+        assert!(Rect::new(0.0, 0.0, f64::NAN, 1.0).area().is_nan());
+    }
+
+    #[test]
+    fn rect_intersection() {
+        let a = Rect::new(0.0, 0.0, 20.0, 20.0);
+        let b = Rect::new(10.0, 5.0, 30.0, 15.0);
+        assert_eq!(Some(Rect::new(10.0, 5.0, 20.0, 15.0)), a.intersection(&b));
+        assert_eq!(a.intersection(&b), b.intersection(&a));
+        assert!(a.intersects(&b));
+        assert_eq!(Some(b), b.intersection(&b));
+
+        // Touching edges and disjoint rectangles.
+        assert!(!a.intersects(&Rect::new(20.0, 0.0, 40.0, 20.0)));
+        assert!(!a.intersects(&Rect::new(30.0, 30.0, 40.0, 40.0)));
+
+        // Zero-area rectangles, even inside the other rectangle.
+        assert!(!a.intersects(&Rect::new(5.0, 5.0, 5.0, 15.0)));
+        assert!(!a.intersects(&Rect::ZERO));
+
+        // Negative width or height.
+        assert!(!a.intersects(&Rect::new(15.0, 5.0, 5.0, 15.0)));
+        assert!(!Rect::new(15.0, 15.0, 5.0, 5.0).intersects(&a));
+
+        // NaN coordinates.
+        assert!(!a.intersects(&Rect::new(f64::NAN, 5.0, 15.0, 15.0)));
+        assert!(!Rect::new(0.0, 0.0, 20.0, f64::NAN).intersects(&b));
+    }
+
+    #[test]
+    fn rect_union() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(20.0, -5.0, 30.0, 5.0);
+        assert_eq!(Rect::new(0.0, -5.0, 30.0, 10.0), a.union(b));
+        assert_eq!(a.union(b), b.union(a));
+        assert_eq!(a, a.union(Rect::new(5.0, 5.0, 5.0, 5.0)));
+        assert_eq!(
+            Rect::new(-1.0, 0.0, 10.0, 10.0),
+            a.union_pt(Point::new(-1.0, 0.0))
+        );
+    }
+}
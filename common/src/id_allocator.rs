@@ -0,0 +1,180 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use std::sync::Mutex;
+
+use crate::NodeId;
+
+/// Generates random node IDs that are unique across processes without
+/// any coordination, for applications that synchronize accessibility trees
+/// between multiple processes or machines.
+///
+/// The generated IDs are never zero. Since each ID is drawn uniformly from
+/// the 64-bit space, the probability of a collision is negligible for any
+/// realistic number of nodes.
+pub struct UuidNodeIdAllocator;
+
+impl UuidNodeIdAllocator {
+    /// Generates a new ID from 16 bytes provided by the operating system's
+    /// cryptographically secure random number generator.
+    ///
+    /// Panics if the operating system's random number generator fails.
+    #[cfg(feature = "getrandom")]
+    pub fn allocate() -> NodeId {
+        loop {
+            let mut bytes = [0u8; 16];
+            if let Err(error) = getrandom::getrandom(&mut bytes) {
+                panic!("Failed to generate a random node ID: {}", error);
+            }
+            if let Some(id) = NodeId::from_uuid_bytes(bytes) {
+                return id;
+            }
+        }
+    }
+
+    /// Generates an ID that only depends on the given seed. This is meant
+    /// for tests, which need reproducible IDs. As with `allocate`,
+    /// the result is never zero, and IDs generated from different seeds
+    /// are as unlikely to collide as random ones.
+    pub fn allocate_deterministic(seed: u64) -> NodeId {
+        // SplitMix64, retrying if the result is zero.
+        let mut state = seed;
+        loop {
+            state = state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^= z >> 31;
+            if z != 0 {
+                return NodeId(z);
+            }
+        }
+    }
+}
+
+/// Allocates sequential node IDs, reusing IDs that have been freed.
+///
+/// This is useful for applications that frequently create and destroy
+/// nodes, such as the items of a virtualized list. Freed IDs are reused
+/// in last-in, first-out order. The allocator can be shared between threads.
+///
+/// The allocator doesn't know which IDs are in use by the tree. An ID
+/// must only be freed once the node has been removed from the tree.
+#[derive(Debug, Default)]
+pub struct RecyclingIdAllocator {
+    state: Mutex<(u64, Vec<u64>)>,
+}
+
+impl RecyclingIdAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the most recently freed ID, or the next unused ID
+    /// if no IDs have been freed.
+    pub fn allocate(&self) -> NodeId {
+        let mut state = self.state.lock().unwrap();
+        let (next, free_list) = &mut *state;
+        if let Some(id) = free_list.pop() {
+            return NodeId(id);
+        }
+        let id = *next;
+        *next = next.checked_add(1).expect("node ID space exhausted");
+        NodeId(id)
+    }
+
+    /// Makes the given ID available to be allocated again.
+    pub fn free(&self, id: NodeId) {
+        self.state.lock().unwrap().1.push(id.0);
+    }
+
+    /// Makes all of the given IDs available to be allocated again.
+    pub fn free_many(&self, ids: impl IntoIterator<Item = NodeId>) {
+        self.state
+            .lock()
+            .unwrap()
+            .1
+            .extend(ids.into_iter().map(|id| id.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc, thread};
+
+    use super::{RecyclingIdAllocator, UuidNodeIdAllocator};
+    use crate::NodeId;
+
+    #[test]
+    fn no_collisions() {
+        const COUNT: u64 = 1_000_000;
+        let ids = (0..COUNT)
+            .map(UuidNodeIdAllocator::allocate_deterministic)
+            .collect::<HashSet<NodeId>>();
+        assert_eq!(COUNT as usize, ids.len());
+        assert!(!ids.contains(&NodeId(0)));
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn random_ids_are_unique_and_non_zero() {
+        const COUNT: usize = 10_000;
+        let ids = (0..COUNT)
+            .map(|_| UuidNodeIdAllocator::allocate())
+            .collect::<HashSet<NodeId>>();
+        assert_eq!(COUNT, ids.len());
+        assert!(!ids.contains(&NodeId(0)));
+    }
+
+    #[test]
+    fn from_uuid_bytes() {
+        assert_eq!(None, NodeId::from_uuid_bytes([0; 16]));
+        assert_eq!(
+            Some(NodeId(3)),
+            NodeId::from_uuid_bytes([1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn recycling_allocator_reuses_freed_ids_lifo() {
+        let allocator = RecyclingIdAllocator::new();
+        assert_eq!(NodeId(0), allocator.allocate());
+        assert_eq!(NodeId(1), allocator.allocate());
+        assert_eq!(NodeId(2), allocator.allocate());
+        allocator.free(NodeId(0));
+        allocator.free(NodeId(2));
+        assert_eq!(NodeId(2), allocator.allocate());
+        assert_eq!(NodeId(0), allocator.allocate());
+        assert_eq!(NodeId(3), allocator.allocate());
+        allocator.free_many([NodeId(1), NodeId(3)]);
+        assert_eq!(NodeId(3), allocator.allocate());
+        assert_eq!(NodeId(1), allocator.allocate());
+        assert_eq!(NodeId(4), allocator.allocate());
+    }
+
+    #[test]
+    fn recycling_allocator_concurrent_allocation() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 1000;
+        let allocator = Arc::new(RecyclingIdAllocator::new());
+        let initial = (0..100).map(|_| allocator.allocate()).collect::<Vec<_>>();
+        allocator.free_many(initial);
+        let handles = (0..THREADS)
+            .map(|_| {
+                let allocator = Arc::clone(&allocator);
+                thread::spawn(move || {
+                    (0..PER_THREAD)
+                        .map(|_| allocator.allocate())
+                        .collect::<Vec<NodeId>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let ids = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<HashSet<NodeId>>();
+        assert_eq!(THREADS * PER_THREAD, ids.len());
+    }
+}
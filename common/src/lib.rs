@@ -27,6 +27,9 @@ use std::fmt;
 mod geometry;
 pub use geometry::{Affine, Point, Rect, Size, Vec2};
 
+mod id_allocator;
+pub use id_allocator::{RecyclingIdAllocator, UuidNodeIdAllocator};
+
 /// The type of an accessibility node.
 ///
 /// The majority of these roles come from the ARIA specification. Reference
@@ -655,6 +658,19 @@ impl From<NodeId> for NodeIdContent {
     }
 }
 
+impl NodeId {
+    /// Derives a node ID from the 16 bytes of a UUID generated elsewhere.
+    /// Because a node ID is only 64 bits wide, the two halves of the UUID
+    /// are combined, which preserves the negligible collision probability
+    /// of random (version 4) UUIDs. Returns `None` if the result is zero,
+    /// e.g. for the nil UUID.
+    pub fn from_uuid_bytes(bytes: [u8; 16]) -> Option<NodeId> {
+        let value = u128::from_le_bytes(bytes);
+        let folded = (value as u64) ^ ((value >> 64) as u64);
+        (folded != 0).then_some(NodeId(folded))
+    }
+}
+
 /// Defines a custom action for a UI element.
 ///
 /// For example, a list UI can allow a user to reorder items in the list by dragging the
@@ -726,6 +742,8 @@ enum Flag {
     IsGrammarError,
     IsSearchMatch,
     IsSuggestion,
+    HasUnloadedChildren,
+    Protected,
 }
 
 impl Flag {
@@ -839,6 +857,7 @@ enum PropertyId {
     Level,
     SizeOfSet,
     PositionInSet,
+    ZIndex,
 
     // Color
     ColorValue,
@@ -928,7 +947,12 @@ struct PropertiesBuilder {
 }
 
 /// Builds a [`Node`].
-#[derive(Clone, Default, PartialEq)]
+///
+/// The default builder has the role [`Role::GenericContainer`].
+/// Such nodes are usually ignored by assistive technologies, so this is
+/// only meant as a placeholder; set the actual role, e.g. with
+/// [`NodeBuilder::with_role`], before building the node.
+#[derive(Clone, PartialEq)]
 pub struct NodeBuilder {
     role: Role,
     actions: u32,
@@ -936,6 +960,17 @@ pub struct NodeBuilder {
     properties: PropertiesBuilder,
 }
 
+impl Default for NodeBuilder {
+    fn default() -> Self {
+        Self {
+            role: Role::GenericContainer,
+            actions: 0,
+            flags: 0,
+            properties: PropertiesBuilder::default(),
+        }
+    }
+}
+
 impl PropertyIndices {
     fn get<'a>(&self, values: &'a [PropertyValue], id: PropertyId) -> &'a PropertyValue {
         let index = self.0[id as usize];
@@ -1379,6 +1414,27 @@ impl NodeBuilder {
         }
     }
 
+    #[inline]
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Creates a builder with all of the properties of an existing node,
+    /// so that some of them can be changed without having to set
+    /// the others again.
+    pub fn from_node(node: &Node) -> Self {
+        Self {
+            role: node.role,
+            actions: node.actions,
+            flags: node.flags,
+            properties: PropertiesBuilder {
+                indices: node.properties.indices,
+                values: node.properties.values.to_vec(),
+            },
+        }
+    }
+
     pub fn build(self) -> Node {
         Node {
             role: self.role,
@@ -1467,7 +1523,17 @@ flag_methods! {
     (IsSpellingError, is_spelling_error, set_is_spelling_error, clear_is_spelling_error),
     (IsGrammarError, is_grammar_error, set_is_grammar_error, clear_is_grammar_error),
     (IsSearchMatch, is_search_match, set_is_search_match, clear_is_search_match),
-    (IsSuggestion, is_suggestion, set_is_suggestion, clear_is_suggestion)
+    (IsSuggestion, is_suggestion, set_is_suggestion, clear_is_suggestion),
+    /// Indicates that this node has children which haven't yet been
+    /// included in the tree, e.g. because the provider loads them lazily.
+    /// The provider should still set [`children`] to the children that
+    /// are already present, if any.
+    ///
+    /// [`children`]: Node::children
+    (HasUnloadedChildren, has_unloaded_children, set_has_unloaded_children, clear_has_unloaded_children),
+    /// Indicates that the node's value should not be revealed, e.g. because
+    /// it's a password or PIN. [`Role::PasswordInput`] implies this.
+    (Protected, is_protected, set_protected, clear_protected)
 }
 
 option_ref_type_getters! {
@@ -1622,7 +1688,11 @@ usize_property_methods! {
     (ColumnSpan, column_span, set_column_span, clear_column_span),
     (Level, level, set_level, clear_level),
     (SizeOfSet, size_of_set, set_size_of_set, clear_size_of_set),
-    (PositionInSet, position_in_set, set_position_in_set, clear_position_in_set)
+    (PositionInSet, position_in_set, set_position_in_set, clear_position_in_set),
+    /// The stacking order of this node among its siblings. A node is drawn
+    /// on top of any sibling with a lower value, regardless of document
+    /// order. A node without this property has a value of zero.
+    (ZIndex, z_index, set_z_index, clear_z_index)
 }
 
 color_property_methods! {
@@ -2033,7 +2103,8 @@ impl<'de> Visitor<'de> for PropertiesVisitor {
                     ColumnSpan,
                     Level,
                     SizeOfSet,
-                    PositionInSet
+                    PositionInSet,
+                    ZIndex
                 },
                 Color {
                     ColorValue,
@@ -2181,7 +2252,8 @@ impl JsonSchema for Properties {
                 ColumnSpan,
                 Level,
                 SizeOfSet,
-                PositionInSet
+                PositionInSet,
+                ZIndex
             },
             u32 {
                 ColorValue,
@@ -2321,6 +2393,95 @@ pub struct TreeUpdate {
     pub focus: NodeId,
 }
 
+/// A chainable helper for constructing a [`TreeUpdate`].
+///
+/// If [`TreeUpdateBuilder::focus`] is never called, the focus defaults
+/// to the root passed to [`TreeUpdateBuilder::tree_root`].
+#[derive(Clone, Debug, Default)]
+pub struct TreeUpdateBuilder {
+    nodes: Vec<(NodeId, Node)>,
+    root: Option<NodeId>,
+    app_name: Option<String>,
+    focus: Option<NodeId>,
+}
+
+impl TreeUpdateBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new or updated node to the update.
+    pub fn node(mut self, id: NodeId, node: Node) -> Self {
+        self.nodes.push((id, node));
+        self
+    }
+
+    /// Adds new or updated nodes from any collection of `(id, node)` pairs,
+    /// e.g. a `HashMap` or `BTreeMap`, to the update.
+    pub fn nodes(mut self, nodes: impl IntoIterator<Item = (NodeId, Node)>) -> Self {
+        self.nodes.extend(nodes);
+        self
+    }
+
+    /// Sets the node that has keyboard focus. See [`TreeUpdate::focus`].
+    pub fn focus(mut self, id: NodeId) -> Self {
+        self.focus = Some(id);
+        self
+    }
+
+    /// Includes [`TreeUpdate::tree`] in the update, with the given root.
+    pub fn tree_root(mut self, id: NodeId) -> Self {
+        self.root = Some(id);
+        self
+    }
+
+    /// Sets [`Tree::app_name`]. This requires [`TreeUpdateBuilder::tree_root`]
+    /// to also be called.
+    pub fn with_app_name(mut self, name: impl Into<String>) -> Self {
+        self.app_name = Some(name.into());
+        self
+    }
+
+    /// Builds the update.
+    ///
+    /// # Panics
+    ///
+    /// Panics if neither the focus nor the tree root was set, or if
+    /// an app name was set without a tree root.
+    pub fn build(self) -> TreeUpdate {
+        assert!(
+            self.root.is_some() || self.app_name.is_none(),
+            "an app name can only be set along with the tree root"
+        );
+        let tree = self.root.map(|root| Tree {
+            app_name: self.app_name,
+            ..Tree::new(root)
+        });
+        let focus = self
+            .focus
+            .or(self.root)
+            .expect("either the focus or the tree root must be set");
+        TreeUpdate {
+            nodes: self.nodes,
+            tree,
+            focus,
+        }
+    }
+}
+
+impl Extend<(NodeId, Node)> for TreeUpdateBuilder {
+    fn extend<T: IntoIterator<Item = (NodeId, Node)>>(&mut self, iter: T) {
+        self.nodes.extend(iter);
+    }
+}
+
+impl FromIterator<(NodeId, Node)> for TreeUpdateBuilder {
+    fn from_iter<T: IntoIterator<Item = (NodeId, Node)>>(iter: T) -> Self {
+        Self::new().nodes(iter)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "schemars", derive(JsonSchema))]
@@ -2404,3 +2565,124 @@ pub trait DeactivationHandler {
     /// Refer to the platform adapter documentation for more details.
     fn deactivate_accessibility(&mut self);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_builder_default_role() {
+        assert_eq!(Role::GenericContainer, NodeBuilder::default().role());
+        assert_eq!(
+            NodeBuilder::new(Role::GenericContainer),
+            NodeBuilder::default()
+        );
+        let builders: Vec<NodeBuilder> = vec![NodeBuilder::default(); 2];
+        let nodes = builders
+            .into_iter()
+            .map(|builder| builder.with_role(Role::Button).build())
+            .collect::<Vec<_>>();
+        assert!(nodes.iter().all(|node| node.role() == Role::Button));
+        assert_eq!(Role::Label, NodeBuilder::new(Role::Label).role());
+    }
+
+    #[test]
+    fn node_builder_from_node_round_trip() {
+        let node = {
+            let mut builder = NodeBuilder::new(Role::CheckBox);
+            builder.set_name("Remember me");
+            builder.set_children(vec![NodeId(1), NodeId(2)]);
+            builder.set_bounds(Rect {
+                x0: 0.0,
+                y0: 0.0,
+                x1: 100.0,
+                y1: 20.0,
+            });
+            builder.set_toggled(Toggled::True);
+            builder.set_numeric_value(1.0);
+            builder.set_hidden();
+            builder.add_action(Action::Default);
+            builder.add_action(Action::Focus);
+            builder.set_description("Stay signed in");
+            builder.clear_description();
+            builder.build()
+        };
+        assert_eq!(node, NodeBuilder::from_node(&node).build());
+
+        let mut builder = NodeBuilder::from_node(&node);
+        builder.set_name("Keep me signed in");
+        builder.clear_hidden();
+        let updated = builder.build();
+        assert_eq!(Some("Keep me signed in"), updated.name());
+        assert!(!updated.is_hidden());
+        assert_eq!(node.role(), updated.role());
+        assert_eq!(node.children(), updated.children());
+        assert_eq!(node.bounds(), updated.bounds());
+        assert_eq!(node.toggled(), updated.toggled());
+        assert_eq!(node.numeric_value(), updated.numeric_value());
+        assert!(updated.supports_action(Action::Default));
+        assert!(updated.supports_action(Action::Focus));
+        assert_eq!(None, updated.description());
+    }
+
+    #[test]
+    fn node_id_ordering_matches_content() {
+        let contents: [NodeIdContent; 5] = [42, 0, NodeIdContent::MAX, 7, 1 << 40];
+        let mut ids = contents.map(NodeId);
+        ids.sort();
+        let mut sorted_contents = contents;
+        sorted_contents.sort();
+        assert_eq!(sorted_contents.map(NodeId), ids);
+        assert!(NodeId(1) < NodeId(2));
+        assert_eq!(
+            NodeId(3).cmp(&NodeId(NodeIdContent::MAX)),
+            3.cmp(&NodeIdContent::MAX)
+        );
+    }
+
+    #[test]
+    fn tree_update_builder_nodes_from_collections() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let root = {
+            let mut builder = NodeBuilder::new(Role::Window);
+            builder.set_children(vec![NodeId(1), NodeId(2)]);
+            builder.build()
+        };
+        let button = NodeBuilder::new(Role::Button).build();
+        let label = NodeBuilder::new(Role::Label).build();
+
+        let ordered = BTreeMap::from([
+            (NodeId(2), label.clone()),
+            (NodeId(0), root.clone()),
+            (NodeId(1), button.clone()),
+        ]);
+        let update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .nodes(ordered)
+            .build();
+        assert_eq!(
+            vec![
+                (NodeId(0), root.clone()),
+                (NodeId(1), button.clone()),
+                (NodeId(2), label.clone()),
+            ],
+            update.nodes
+        );
+
+        let unordered = HashMap::from([(NodeId(1), button.clone()), (NodeId(2), label)]);
+        let mut builder = unordered
+            .into_iter()
+            .collect::<TreeUpdateBuilder>()
+            .focus(NodeId(1));
+        builder.extend([(NodeId(0), root)]);
+        let mut update = builder.build();
+        assert_eq!(NodeId(1), update.focus);
+        assert!(update.tree.is_none());
+        update.nodes.sort_by_key(|(id, _)| *id);
+        assert_eq!(
+            vec![NodeId(0), NodeId(1), NodeId(2)],
+            update.nodes.iter().map(|(id, _)| *id).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{Node, NodeBuilder, NodeId, Role, TreeUpdate, TreeUpdateBuilder};
+use accesskit_consumer::Tree;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const ROOT_ID: NodeId = NodeId(0);
+const GROUP_COUNT: u64 = 500;
+const ITEMS_PER_GROUP: u64 = 99;
+const NODES_PER_GROUP: u64 = ITEMS_PER_GROUP + 1;
+const LOOKUP_COUNT: usize = 10_000;
+
+fn group_id(index: u64) -> NodeId {
+    NodeId(1 + index * NODES_PER_GROUP)
+}
+
+fn group(id: NodeId) -> Node {
+    let mut builder = NodeBuilder::new(Role::Group);
+    builder.set_children(
+        (id.0 + 1..id.0 + NODES_PER_GROUP)
+            .map(NodeId)
+            .collect::<Vec<NodeId>>(),
+    );
+    builder.build()
+}
+
+fn item(id: NodeId) -> Node {
+    let mut builder = NodeBuilder::new(Role::ListItem);
+    builder.set_name(format!("Item {}", id.0));
+    builder.build()
+}
+
+fn initial_update() -> TreeUpdate {
+    let root = {
+        let mut builder = NodeBuilder::new(Role::Window);
+        builder.set_children((0..GROUP_COUNT).map(group_id).collect::<Vec<NodeId>>());
+        builder.build()
+    };
+    let mut nodes = vec![(ROOT_ID, root)];
+    for index in 0..GROUP_COUNT {
+        let id = group_id(index);
+        nodes.push((id, group(id)));
+        nodes.extend((id.0 + 1..id.0 + NODES_PER_GROUP).map(|id| (NodeId(id), item(NodeId(id)))));
+    }
+    TreeUpdateBuilder::new()
+        .tree_root(ROOT_ID)
+        .nodes(nodes)
+        .build()
+}
+
+/// Returns pseudo-random IDs of nodes in the tree, from a fixed seed
+/// so that runs are comparable.
+fn lookup_ids() -> Vec<NodeId> {
+    let node_count = GROUP_COUNT * NODES_PER_GROUP + 1;
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    (0..LOOKUP_COUNT)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            NodeId(state % node_count)
+        })
+        .collect()
+}
+
+fn random_lookups(c: &mut Criterion) {
+    let tree = Tree::new(initial_update(), false);
+    let ids = lookup_ids();
+    c.bench_function(
+        "10000 random TreeState::node_by_id calls with 50001 nodes",
+        |b| {
+            b.iter(|| {
+                let state = tree.state();
+                for id in &ids {
+                    black_box(state.node_by_id(*id).unwrap().role());
+                }
+            })
+        },
+    );
+}
+
+criterion_group!(benches, random_lookups);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{Node, NodeBuilder, NodeId, Role, TreeUpdate, TreeUpdateBuilder};
+use accesskit_consumer::{common_filter, Tree};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const ROOT_ID: NodeId = NodeId(0);
+const SECTION_COUNT: u64 = 1000;
+const PARAGRAPHS_PER_SECTION: u64 = 19;
+const NODES_PER_SECTION: u64 = PARAGRAPHS_PER_SECTION + 2;
+
+fn section_id(index: u64) -> NodeId {
+    NodeId(1 + index * NODES_PER_SECTION)
+}
+
+fn section(id: NodeId) -> Node {
+    let mut builder = NodeBuilder::new(Role::Section);
+    builder.set_children(
+        (id.0 + 1..id.0 + NODES_PER_SECTION)
+            .map(NodeId)
+            .collect::<Vec<NodeId>>(),
+    );
+    builder.build()
+}
+
+fn heading(id: NodeId) -> Node {
+    let mut builder = NodeBuilder::new(Role::Heading);
+    builder.set_name(format!("Heading {}", id.0));
+    builder.build()
+}
+
+fn paragraph(id: NodeId) -> Node {
+    let mut builder = NodeBuilder::new(Role::Paragraph);
+    builder.set_name(format!("Paragraph {}", id.0));
+    builder.build()
+}
+
+fn initial_update() -> TreeUpdate {
+    let root = {
+        let mut builder = NodeBuilder::new(Role::Document);
+        builder.set_children((0..SECTION_COUNT).map(section_id).collect::<Vec<NodeId>>());
+        builder.build()
+    };
+    let mut nodes = vec![(ROOT_ID, root)];
+    for index in 0..SECTION_COUNT {
+        let id = section_id(index);
+        nodes.push((id, section(id)));
+        let heading_id = NodeId(id.0 + 1);
+        nodes.push((heading_id, heading(heading_id)));
+        nodes.extend(
+            (heading_id.0 + 1..id.0 + NODES_PER_SECTION)
+                .map(|id| (NodeId(id), paragraph(NodeId(id)))),
+        );
+    }
+    TreeUpdateBuilder::new()
+        .tree_root(ROOT_ID)
+        .nodes(nodes)
+        .build()
+}
+
+fn next_heading(c: &mut Criterion) {
+    let tree = Tree::new(initial_update(), false);
+    let state = tree.state();
+    let start = state
+        .node_by_id(NodeId(section_id(SECTION_COUNT / 2).0 + 2))
+        .unwrap();
+    c.bench_function("Node::next_with_role(Heading) with 21000 nodes", |b| {
+        b.iter(|| black_box(start.next_with_role(Role::Heading, &common_filter)))
+    });
+}
+
+fn nodes_with_role(c: &mut Criterion) {
+    let tree = Tree::new(initial_update(), false);
+    c.bench_function(
+        "TreeState::nodes_with_role(Heading) with 21000 nodes",
+        |b| b.iter(|| black_box(tree.state().nodes_with_role(Role::Heading).count())),
+    );
+}
+
+criterion_group!(benches, next_heading, nodes_with_role);
+criterion_main!(benches);
@@ -0,0 +1,100 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{Node, NodeBuilder, NodeId, Role, TreeUpdate, TreeUpdateBuilder};
+use accesskit_consumer::Tree;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+const ROOT_ID: NodeId = NodeId(0);
+const NODE_COUNT: u64 = 1000;
+const CHANGED_NODE_COUNT: u64 = 100;
+
+fn root(child_count: u64) -> Node {
+    let mut builder = NodeBuilder::new(Role::Window);
+    builder.set_children((1..=child_count).map(NodeId).collect::<Vec<NodeId>>());
+    builder.build()
+}
+
+fn button(id: NodeId, generation: usize) -> Node {
+    let mut builder = NodeBuilder::new(Role::Button);
+    builder.set_name(format!("Button {} ({})", id.0, generation));
+    builder.build()
+}
+
+fn initial_update() -> TreeUpdate {
+    let child_count = NODE_COUNT - 1;
+    let mut nodes = vec![(ROOT_ID, root(child_count))];
+    nodes.extend((1..=child_count).map(|id| (NodeId(id), button(NodeId(id), 0))));
+    TreeUpdateBuilder::new()
+        .tree_root(ROOT_ID)
+        .nodes(nodes)
+        .build()
+}
+
+fn replace_nodes_update() -> TreeUpdate {
+    TreeUpdateBuilder::new()
+        .nodes((1..=CHANGED_NODE_COUNT).map(|id| (NodeId(id), button(NodeId(id), 1))))
+        .focus(ROOT_ID)
+        .build()
+}
+
+fn add_nodes_update() -> TreeUpdate {
+    let child_count = NODE_COUNT - 1 + CHANGED_NODE_COUNT;
+    let mut nodes = vec![(ROOT_ID, root(child_count))];
+    nodes.extend((NODE_COUNT..=child_count).map(|id| (NodeId(id), button(NodeId(id), 0))));
+    TreeUpdateBuilder::new().nodes(nodes).focus(ROOT_ID).build()
+}
+
+fn tree_new(c: &mut Criterion) {
+    c.bench_function("Tree::new with 1000 nodes", |b| {
+        b.iter_batched(
+            initial_update,
+            |update| black_box(Tree::new(update, false)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn tree_update_replace(c: &mut Criterion) {
+    c.bench_function("Tree::update replacing 100 of 1000 nodes", |b| {
+        b.iter_batched(
+            || (Tree::new(initial_update(), false), replace_nodes_update()),
+            |(mut tree, update)| {
+                tree.update(update);
+                black_box(tree)
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn tree_update_add(c: &mut Criterion) {
+    c.bench_function("Tree::update adding 100 nodes to 1000 nodes", |b| {
+        b.iter_batched(
+            || (Tree::new(initial_update(), false), add_nodes_update()),
+            |(mut tree, update)| {
+                tree.update(update);
+                black_box(tree)
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn tree_state_serialize(c: &mut Criterion) {
+    let tree = Tree::new(initial_update(), false);
+    c.bench_function("TreeState::serialize with 1000 nodes", |b| {
+        b.iter(|| black_box(tree.state().serialize()))
+    });
+}
+
+criterion_group!(
+    benches,
+    tree_new,
+    tree_update_replace,
+    tree_update_add,
+    tree_state_serialize
+);
+criterion_main!(benches);
@@ -0,0 +1,107 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::NodeId;
+use std::fmt::Write;
+
+use crate::{node::Node, tree::State as TreeState};
+
+/// Returns a human-readable representation of the whole tree, with one
+/// node per line, indented by depth. Each line shows the node's ID, role,
+/// name, and bounding box, if any.
+pub fn debug_print_tree(state: &TreeState) -> String {
+    debug_print_node(state, state.root_id(), usize::MAX)
+}
+
+/// Returns a human-readable representation of the subtree rooted at the
+/// given node, in the same format as [`debug_print_tree`], descending
+/// at most `max_depth` levels below that node. Returns an empty string
+/// if the node isn't in the tree.
+pub fn debug_print_node(state: &TreeState, id: NodeId, max_depth: usize) -> String {
+    let mut result = String::new();
+    if let Some(node) = state.node_by_id(id) {
+        print_subtree(&mut result, &node, 0, max_depth);
+    }
+    result
+}
+
+fn print_subtree(result: &mut String, node: &Node, depth: usize, max_depth: usize) {
+    write!(
+        result,
+        "{:indent$}#{} {:?}",
+        "",
+        node.id().0,
+        node.role(),
+        indent = depth * 2
+    )
+    .unwrap();
+    if let Some(name) = node.name() {
+        write!(result, " {:?}", name).unwrap();
+    }
+    if let Some(rect) = node.bounding_box() {
+        write!(
+            result,
+            " ({}, {}, {}, {})",
+            rect.x0, rect.y0, rect.x1, rect.y1
+        )
+        .unwrap();
+    }
+    result.push('\n');
+    if depth == max_depth {
+        return;
+    }
+    for child in node.children() {
+        print_subtree(result, &child, depth + 1, max_depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{NodeBuilder, NodeId, Rect, Role, TreeUpdateBuilder};
+
+    use crate::tests::*;
+
+    #[test]
+    fn print_tree() {
+        let update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NodeId(1), NodeId(2)]);
+                builder.build()
+            })
+            .node(NodeId(1), {
+                let mut builder = NodeBuilder::new(Role::Button);
+                builder.set_name("OK");
+                builder.set_bounds(Rect {
+                    x0: 10.0,
+                    y0: 10.0,
+                    x1: 90.0,
+                    y1: 30.0,
+                });
+                builder.build()
+            })
+            .node(NodeId(2), NodeBuilder::new(Role::GenericContainer).build())
+            .build();
+        let tree = crate::Tree::new(update, false);
+        let expected = "#0 Window\n  #1 Button \"OK\" (10, 10, 90, 30)\n  #2 GenericContainer\n";
+        assert_eq!(expected, super::debug_print_tree(tree.state()));
+        assert_eq!(expected, tree.state().debug_print());
+    }
+
+    #[test]
+    fn print_node_with_max_depth() {
+        let tree = test_tree();
+        assert_eq!(
+            "#3 Paragraph (10, 40, 810, 80)\n  #4 Button \"button_1_0_hidden\"\n  #6 Label \"label_1_1\" (20, 50, 100, 70)\n  #7 Button \"button_1_2_hidden\"\n",
+            super::debug_print_node(tree.state(), PARAGRAPH_1_IGNORED_ID, 1)
+        );
+        assert_eq!(
+            "#3 Paragraph (10, 40, 810, 80)\n",
+            super::debug_print_node(tree.state(), PARAGRAPH_1_IGNORED_ID, 0)
+        );
+        assert!(super::debug_print_node(tree.state(), NodeId(100), 1).is_empty());
+    }
+}
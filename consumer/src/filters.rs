@@ -14,6 +14,22 @@ pub enum FilterResult {
     ExcludeSubtree,
 }
 
+/// Applies `filter` to a node reached during filtered traversal,
+/// reporting excluded subtrees when the `tracing` feature is enabled.
+#[inline]
+pub(crate) fn apply_filter(filter: &impl Fn(&Node) -> FilterResult, node: &Node) -> FilterResult {
+    let result = filter(node);
+    #[cfg(feature = "tracing")]
+    if result == FilterResult::ExcludeSubtree {
+        tracing::trace!(
+            node = node.id().0,
+            role = ?node.role(),
+            "excluding subtree from filtered traversal"
+        );
+    }
+    result
+}
+
 pub fn common_filter(node: &Node) -> FilterResult {
     if node.is_focused() {
         return FilterResult::Include;
@@ -12,7 +12,11 @@ use std::iter::FusedIterator;
 
 use accesskit::NodeId;
 
-use crate::{filters::FilterResult, node::Node, tree::State as TreeState};
+use crate::{
+    filters::{apply_filter, FilterResult},
+    node::Node,
+    tree::State as TreeState,
+};
 
 /// An iterator that yields following siblings of a node.
 ///
@@ -186,14 +190,14 @@ fn next_filtered_sibling<'a>(
     let mut consider_children = false;
     while let Some(current) = next {
         if let Some(Some(child)) = consider_children.then(|| current.children().next()) {
-            let result = filter(&child);
+            let result = apply_filter(filter, &child);
             next = Some(child);
             if result == FilterResult::Include {
                 return next;
             }
             consider_children = result == FilterResult::ExcludeNode;
         } else if let Some(sibling) = current.following_siblings().next() {
-            let result = filter(&sibling);
+            let result = apply_filter(filter, &sibling);
             next = Some(sibling);
             if result == FilterResult::Include {
                 return next;
@@ -225,14 +229,14 @@ fn previous_filtered_sibling<'a>(
     let mut consider_children = false;
     while let Some(current) = previous {
         if let Some(Some(child)) = consider_children.then(|| current.children().next_back()) {
-            let result = filter(&child);
+            let result = apply_filter(filter, &child);
             previous = Some(child);
             if result == FilterResult::Include {
                 return previous;
             }
             consider_children = result == FilterResult::ExcludeNode;
         } else if let Some(sibling) = current.preceding_siblings().next() {
-            let result = filter(&sibling);
+            let result = apply_filter(filter, &sibling);
             previous = Some(sibling);
             if result == FilterResult::Include {
                 return previous;
@@ -432,6 +436,78 @@ impl<'a, Filter: Fn(&Node) -> FilterResult> DoubleEndedIterator for FilteredChil
 
 impl<'a, Filter: Fn(&Node) -> FilterResult> FusedIterator for FilteredChildren<'a, Filter> {}
 
+/// An iterator that yields a node and all of its descendants,
+/// in pre-order depth-first order.
+///
+/// This struct is created by the [`iter_subtree`](TreeState::iter_subtree) method on [`TreeState`].
+pub struct SubtreeNodes<'a> {
+    tree_state: &'a TreeState,
+    stack: Vec<NodeId>,
+}
+
+impl<'a> SubtreeNodes<'a> {
+    pub(crate) fn new(tree_state: &'a TreeState, root: NodeId) -> Self {
+        let stack = if tree_state.has_node(root) {
+            vec![root]
+        } else {
+            Vec::new()
+        };
+        Self { tree_state, stack }
+    }
+}
+
+impl<'a> Iterator for SubtreeNodes<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.stack.pop()?;
+        let node = self.tree_state.node_by_id(id).unwrap();
+        self.stack.extend(node.data().children().iter().rev());
+        Some(node)
+    }
+}
+
+impl<'a> FusedIterator for SubtreeNodes<'a> {}
+
+/// An iterator that yields a node and its descendants according to
+/// the specified filter, in pre-order depth-first order.
+///
+/// This struct is created by the [`iter_subtree_filtered`](TreeState::iter_subtree_filtered) method on [`TreeState`].
+pub struct FilteredSubtreeNodes<'a, Filter: Fn(&Node) -> FilterResult> {
+    filter: Filter,
+    inner: SubtreeNodes<'a>,
+}
+
+impl<'a, Filter: Fn(&Node) -> FilterResult> FilteredSubtreeNodes<'a, Filter> {
+    pub(crate) fn new(tree_state: &'a TreeState, root: NodeId, filter: Filter) -> Self {
+        Self {
+            filter,
+            inner: SubtreeNodes::new(tree_state, root),
+        }
+    }
+}
+
+impl<'a, Filter: Fn(&Node) -> FilterResult> Iterator for FilteredSubtreeNodes<'a, Filter> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(id) = self.inner.stack.pop() {
+            let node = self.inner.tree_state.node_by_id(id).unwrap();
+            let result = apply_filter(&self.filter, &node);
+            if result == FilterResult::ExcludeSubtree {
+                continue;
+            }
+            self.inner.stack.extend(node.data().children().iter().rev());
+            if result == FilterResult::Include {
+                return Some(node);
+            }
+        }
+        None
+    }
+}
+
+impl<'a, Filter: Fn(&Node) -> FilterResult> FusedIterator for FilteredSubtreeNodes<'a, Filter> {}
+
 pub(crate) enum LabelledBy<'a, Filter: Fn(&Node) -> FilterResult> {
     FromDescendants(FilteredChildren<'a, Filter>),
     Explicit {
@@ -834,4 +910,86 @@ mod tests {
             .next_back()
             .is_none());
     }
+
+    #[test]
+    fn subtree() {
+        let tree = test_tree();
+        assert_eq!(
+            [
+                ROOT_ID,
+                PARAGRAPH_0_ID,
+                LABEL_0_0_IGNORED_ID,
+                PARAGRAPH_1_IGNORED_ID,
+                BUTTON_1_0_HIDDEN_ID,
+                CONTAINER_1_0_0_HIDDEN_ID,
+                LABEL_1_1_ID,
+                BUTTON_1_2_HIDDEN_ID,
+                CONTAINER_1_2_0_HIDDEN_ID,
+                PARAGRAPH_2_ID,
+                LABEL_2_0_ID,
+                PARAGRAPH_3_IGNORED_ID,
+                EMPTY_CONTAINER_3_0_IGNORED_ID,
+                LINK_3_1_IGNORED_ID,
+                LABEL_3_1_0_ID,
+                BUTTON_3_2_ID,
+                EMPTY_CONTAINER_3_3_IGNORED_ID
+            ],
+            tree.state()
+                .iter_subtree(ROOT_ID)
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()[..]
+        );
+        assert_eq!(
+            [
+                PARAGRAPH_3_IGNORED_ID,
+                EMPTY_CONTAINER_3_0_IGNORED_ID,
+                LINK_3_1_IGNORED_ID,
+                LABEL_3_1_0_ID,
+                BUTTON_3_2_ID,
+                EMPTY_CONTAINER_3_3_IGNORED_ID
+            ],
+            tree.state()
+                .iter_subtree(PARAGRAPH_3_IGNORED_ID)
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()[..]
+        );
+        assert_eq!(
+            [LABEL_2_0_ID],
+            tree.state()
+                .iter_subtree(LABEL_2_0_ID)
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()[..]
+        );
+        assert!(tree.state().iter_subtree(NodeId(1000)).next().is_none());
+    }
+
+    #[test]
+    fn filtered_subtree() {
+        let tree = test_tree();
+        assert_eq!(
+            [
+                ROOT_ID,
+                PARAGRAPH_0_ID,
+                LABEL_1_1_ID,
+                PARAGRAPH_2_ID,
+                LABEL_2_0_ID,
+                LABEL_3_1_0_ID,
+                BUTTON_3_2_ID
+            ],
+            tree.state()
+                .iter_subtree_filtered(ROOT_ID, test_tree_filter)
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()[..]
+        );
+        assert!(tree
+            .state()
+            .iter_subtree_filtered(BUTTON_1_0_HIDDEN_ID, test_tree_filter)
+            .next()
+            .is_none());
+        assert!(tree
+            .state()
+            .iter_subtree_filtered(NodeId(1000), test_tree_filter)
+            .next()
+            .is_none());
+    }
 }
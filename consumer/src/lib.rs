@@ -4,27 +4,40 @@
 // the LICENSE-MIT file), at your option.
 
 pub(crate) mod tree;
-pub use tree::{ChangeHandler as TreeChangeHandler, State as TreeState, Tree};
+pub use tree::{ChangeHandler as TreeChangeHandler, ReplaceSubtreeError, State as TreeState, Tree};
 
 pub(crate) mod node;
-pub use node::Node;
+pub use node::{fields_changed, ActionVerb, ChangedNodeFields, Node};
 
 pub(crate) mod filters;
 pub use filters::{common_filter, common_filter_with_root_exception, FilterResult};
 
 pub(crate) mod iterators;
 
+pub(crate) mod table;
+
+pub(crate) mod search;
+pub use search::{TextSearchMatch, TextSearchOptions};
+
+pub(crate) mod snapshot;
+pub use snapshot::{RestoreError, SubtreeSnapshot};
+
 pub(crate) mod text;
 pub use text::{
     AttributeValue as TextAttributeValue, Position as TextPosition, Range as TextRange,
     WeakRange as WeakTextRange,
 };
 
+#[cfg(feature = "debug")]
+pub(crate) mod debug;
+#[cfg(feature = "debug")]
+pub use debug::{debug_print_node, debug_print_tree};
+
 #[cfg(test)]
 mod tests {
-    use accesskit::{Affine, NodeBuilder, NodeId, Rect, Role, Tree, TreeUpdate, Vec2};
+    use accesskit::{Affine, Node, NodeBuilder, NodeId, Rect, Role, TreeUpdateBuilder, Vec2};
 
-    use crate::FilterResult;
+    use crate::{FilterResult, TreeChangeHandler};
 
     pub const ROOT_ID: NodeId = NodeId(0);
     pub const PARAGRAPH_0_ID: NodeId = NodeId(1);
@@ -154,29 +167,26 @@ mod tests {
             builder.build()
         };
         let empty_container_3_3_ignored = NodeBuilder::new(Role::GenericContainer).build();
-        let initial_update = TreeUpdate {
-            nodes: vec![
-                (ROOT_ID, root),
-                (PARAGRAPH_0_ID, paragraph_0),
-                (LABEL_0_0_IGNORED_ID, label_0_0_ignored),
-                (PARAGRAPH_1_IGNORED_ID, paragraph_1_ignored),
-                (BUTTON_1_0_HIDDEN_ID, button_1_0_hidden),
-                (CONTAINER_1_0_0_HIDDEN_ID, container_1_0_0_hidden),
-                (LABEL_1_1_ID, label_1_1),
-                (BUTTON_1_2_HIDDEN_ID, button_1_2_hidden),
-                (CONTAINER_1_2_0_HIDDEN_ID, container_1_2_0_hidden),
-                (PARAGRAPH_2_ID, paragraph_2),
-                (LABEL_2_0_ID, label_2_0),
-                (PARAGRAPH_3_IGNORED_ID, paragraph_3_ignored),
-                (EMPTY_CONTAINER_3_0_IGNORED_ID, empty_container_3_0_ignored),
-                (LINK_3_1_IGNORED_ID, link_3_1_ignored),
-                (LABEL_3_1_0_ID, label_3_1_0),
-                (BUTTON_3_2_ID, button_3_2),
-                (EMPTY_CONTAINER_3_3_IGNORED_ID, empty_container_3_3_ignored),
-            ],
-            tree: Some(Tree::new(ROOT_ID)),
-            focus: ROOT_ID,
-        };
+        let initial_update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, root)
+            .node(PARAGRAPH_0_ID, paragraph_0)
+            .node(LABEL_0_0_IGNORED_ID, label_0_0_ignored)
+            .node(PARAGRAPH_1_IGNORED_ID, paragraph_1_ignored)
+            .node(BUTTON_1_0_HIDDEN_ID, button_1_0_hidden)
+            .node(CONTAINER_1_0_0_HIDDEN_ID, container_1_0_0_hidden)
+            .node(LABEL_1_1_ID, label_1_1)
+            .node(BUTTON_1_2_HIDDEN_ID, button_1_2_hidden)
+            .node(CONTAINER_1_2_0_HIDDEN_ID, container_1_2_0_hidden)
+            .node(PARAGRAPH_2_ID, paragraph_2)
+            .node(LABEL_2_0_ID, label_2_0)
+            .node(PARAGRAPH_3_IGNORED_ID, paragraph_3_ignored)
+            .node(EMPTY_CONTAINER_3_0_IGNORED_ID, empty_container_3_0_ignored)
+            .node(LINK_3_1_IGNORED_ID, link_3_1_ignored)
+            .node(LABEL_3_1_0_ID, label_3_1_0)
+            .node(BUTTON_3_2_ID, button_3_2)
+            .node(EMPTY_CONTAINER_3_3_IGNORED_ID, empty_container_3_3_ignored)
+            .build();
         crate::tree::Tree::new(initial_update, false)
     }
 
@@ -196,4 +206,80 @@ mod tests {
             FilterResult::Include
         }
     }
+
+    pub fn node_with_children(role: Role, children: Vec<NodeId>) -> Node {
+        let mut builder = NodeBuilder::new(role);
+        builder.set_children(children);
+        builder.build()
+    }
+
+    /// A change reported to a [`ChangeRecorder`], identified by the IDs
+    /// of the nodes involved.
+    #[derive(Debug, PartialEq)]
+    pub enum Change {
+        Added(NodeId),
+        Updated(NodeId),
+        FocusMoved(Option<NodeId>, Option<NodeId>),
+        Removed(NodeId),
+        BusyStateChanged(NodeId, bool),
+        TitleChanged(Option<String>),
+        ActiveDescendantChanged(NodeId, Option<NodeId>, Option<NodeId>),
+    }
+
+    /// A change handler that records every change it's given, in order.
+    #[derive(Default)]
+    pub struct ChangeRecorder {
+        pub changes: Vec<Change>,
+    }
+
+    impl ChangeRecorder {
+        /// Returns the changes recorded so far and clears the record.
+        pub fn take(&mut self) -> Vec<Change> {
+            std::mem::take(&mut self.changes)
+        }
+    }
+
+    impl TreeChangeHandler for ChangeRecorder {
+        fn node_added(&mut self, node: &crate::Node) {
+            self.changes.push(Change::Added(node.id()));
+        }
+
+        fn node_updated(&mut self, _old_node: &crate::Node, new_node: &crate::Node) {
+            self.changes.push(Change::Updated(new_node.id()));
+        }
+
+        fn focus_moved(&mut self, old_node: Option<&crate::Node>, new_node: Option<&crate::Node>) {
+            self.changes.push(Change::FocusMoved(
+                old_node.map(|node| node.id()),
+                new_node.map(|node| node.id()),
+            ));
+        }
+
+        fn node_removed(&mut self, node: &crate::Node) {
+            self.changes.push(Change::Removed(node.id()));
+        }
+
+        fn busy_state_changed(&mut self, node: &crate::Node) {
+            self.changes
+                .push(Change::BusyStateChanged(node.id(), node.is_busy()));
+        }
+
+        fn title_changed(&mut self, title: Option<&str>) {
+            self.changes
+                .push(Change::TitleChanged(title.map(String::from)));
+        }
+
+        fn active_descendant_changed(
+            &mut self,
+            focus: &crate::Node,
+            old_node: Option<&crate::Node>,
+            new_node: Option<&crate::Node>,
+        ) {
+            self.changes.push(Change::ActiveDescendantChanged(
+                focus.id(),
+                old_node.map(|node| node.id()),
+                new_node.map(|node| node.id()),
+            ));
+        }
+    }
 }
@@ -11,11 +11,11 @@
 use std::{iter::FusedIterator, sync::Arc};
 
 use accesskit::{
-    Action, Affine, DefaultActionVerb, Live, Node as NodeData, NodeId, Orientation, Point, Rect,
-    Role, TextSelection, Toggled,
+    Action, Affine, AriaCurrent, DefaultActionVerb, Invalid, Live, Node as NodeData, NodeId,
+    Orientation, Point, Rect, Role, TextSelection, Toggled,
 };
 
-use crate::filters::FilterResult;
+use crate::filters::{apply_filter, common_filter, FilterResult};
 use crate::iterators::{
     FilteredChildren, FollowingFilteredSiblings, FollowingSiblings, LabelledBy,
     PrecedingFilteredSiblings, PrecedingSiblings,
@@ -31,6 +31,132 @@ pub(crate) struct NodeState {
     pub(crate) data: Arc<NodeData>,
 }
 
+impl NodeState {
+    pub(crate) fn has_unloaded_children(&self) -> bool {
+        self.data.has_unloaded_children()
+    }
+
+    pub(crate) fn changed_fields(&self, new: &NodeState) -> ChangedNodeFields {
+        let mut changed = ChangedNodeFields::default();
+        if self.parent_and_index != new.parent_and_index {
+            changed.insert(ChangedNodeFields::PARENT);
+        }
+        let (old_data, new_data) = (&*self.data, &*new.data);
+        if Arc::ptr_eq(&self.data, &new.data) || old_data == new_data {
+            return changed;
+        }
+        changed.insert(ChangedNodeFields::DATA);
+        if old_data.role() != new_data.role() {
+            changed.insert(ChangedNodeFields::ROLE);
+        }
+        if old_data.children() != new_data.children() {
+            changed.insert(ChangedNodeFields::CHILDREN);
+        }
+        if old_data.name() != new_data.name() {
+            changed.insert(ChangedNodeFields::NAME);
+        }
+        if old_data.description() != new_data.description() {
+            changed.insert(ChangedNodeFields::DESCRIPTION);
+        }
+        if old_data.value() != new_data.value()
+            || old_data.numeric_value() != new_data.numeric_value()
+        {
+            changed.insert(ChangedNodeFields::VALUE);
+        }
+        if old_data.toggled() != new_data.toggled() {
+            changed.insert(ChangedNodeFields::TOGGLED);
+        }
+        if old_data.bounds() != new_data.bounds() || old_data.transform() != new_data.transform() {
+            changed.insert(ChangedNodeFields::BOUNDS);
+        }
+        if old_data.custom_actions() != new_data.custom_actions() {
+            changed.insert(ChangedNodeFields::CUSTOM_ACTIONS);
+        }
+        if old_data.active_descendant() != new_data.active_descendant() {
+            changed.insert(ChangedNodeFields::ACTIVE_DESCENDANT);
+        }
+        changed
+    }
+}
+
+/// A set of flags describing which parts of a node differ between
+/// two versions of it. See [`fields_changed`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ChangedNodeFields(u16);
+
+impl ChangedNodeFields {
+    /// The node's parent, or its index within the parent, changed.
+    pub const PARENT: Self = Self(1 << 0);
+    /// Any property of the node changed. This is set along with
+    /// all of the more specific flags below, and also when a property
+    /// without its own flag changed.
+    pub const DATA: Self = Self(1 << 1);
+    pub const ROLE: Self = Self(1 << 2);
+    pub const CHILDREN: Self = Self(1 << 3);
+    pub const NAME: Self = Self(1 << 4);
+    pub const DESCRIPTION: Self = Self(1 << 5);
+    /// The text or numeric value changed.
+    pub const VALUE: Self = Self(1 << 6);
+    pub const TOGGLED: Self = Self(1 << 7);
+    /// The bounding box or transform changed.
+    pub const BOUNDS: Self = Self(1 << 8);
+    /// Whether the node is the current item changed. See [`Node::is_current`].
+    pub const CURRENT: Self = Self(1 << 9);
+    /// The set of custom actions, their order, or any of their
+    /// descriptions changed.
+    pub const CUSTOM_ACTIONS: Self = Self(1 << 10);
+    /// The active descendant moved to another node. See
+    /// [`Node::active_descendant`].
+    pub const ACTIVE_DESCENDANT: Self = Self(1 << 11);
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+impl std::ops::BitOr for ChangedNodeFields {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Returns which parts of a node differ between two versions of it,
+/// e.g. the old and new nodes passed to
+/// [`ChangeHandler::node_updated`](crate::TreeChangeHandler::node_updated).
+/// The nodes are compared as-is; the IDs aren't checked.
+pub fn fields_changed(old: &Node, new: &Node) -> ChangedNodeFields {
+    let mut changed = old.state.changed_fields(new.state);
+    if old.is_current() != new.is_current() {
+        changed.insert(ChangedNodeFields::CURRENT);
+    }
+    changed
+}
+
+/// A human-meaningful description of what a node's default action does,
+/// as returned by [`Node::action_verb`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ActionVerb {
+    Click,
+    Toggle,
+    Check,
+    Uncheck,
+    Expand,
+    Collapse,
+    Jump,
+    Adjust,
+    Open,
+}
+
 #[derive(Copy, Clone)]
 pub struct Node<'a> {
     pub tree_state: &'a TreeState,
@@ -55,6 +181,25 @@ impl<'a> Node<'a> {
         self.supports_action(Action::Focus) || self.is_focused_in_tree()
     }
 
+    /// Returns the node that this composite widget, such as a list box
+    /// or grid, indicates as its active item while it keeps input focus
+    /// itself, if that node is in the tree. See
+    /// [`TreeState::effective_focus`].
+    pub fn active_descendant(&self) -> Option<Node<'a>> {
+        self.data()
+            .active_descendant()
+            .and_then(|id| self.tree_state.node_by_id(id))
+    }
+
+    /// Returns true if the focused node is a descendant of this node,
+    /// not counting the node itself. Like [`Node::is_focused`], this
+    /// takes into account whether the tree's host has focus.
+    pub fn is_focused_ancestor(&self) -> bool {
+        self.tree_state
+            .focus()
+            .is_some_and(|focus| focus.id() != self.id() && focus.is_descendant_of(self))
+    }
+
     pub fn is_root(&self) -> bool {
         // Don't check for absence of a parent node, in case a non-root node
         // somehow gets detached from the tree.
@@ -73,6 +218,9 @@ impl<'a> Node<'a> {
             .map(|id| self.tree_state.node_by_id(id).unwrap())
     }
 
+    /// Returns the nearest ancestor that the filter includes, skipping
+    /// ancestors that it excludes for any reason, or `None` if there is
+    /// no such ancestor.
     pub fn filtered_parent(&self, filter: &impl Fn(&Node) -> FilterResult) -> Option<Node<'a>> {
         self.parent().and_then(move |parent| {
             if filter(&parent) == FilterResult::Include {
@@ -83,6 +231,24 @@ impl<'a> Node<'a> {
         })
     }
 
+    /// Returns the nearest ancestor that has the given role and that
+    /// the filter includes, as found by repeatedly calling
+    /// [`Node::filtered_parent`].
+    pub fn filtered_ancestor_of_role(
+        &self,
+        role: Role,
+        filter: &impl Fn(&Node) -> FilterResult,
+    ) -> Option<Node<'a>> {
+        let mut current = self.filtered_parent(filter);
+        while let Some(ancestor) = current {
+            if ancestor.role() == role {
+                return Some(ancestor);
+            }
+            current = ancestor.filtered_parent(filter);
+        }
+        None
+    }
+
     pub fn parent_and_index(self) -> Option<(Node<'a>, usize)> {
         self.state
             .parent_and_index
@@ -92,6 +258,23 @@ impl<'a> Node<'a> {
             })
     }
 
+    /// Returns true if the provider has indicated that this node has
+    /// children which aren't yet present in the tree. Until they're loaded,
+    /// those children are simply absent from all child iterators.
+    pub fn has_unloaded_children(&self) -> bool {
+        self.state.has_unloaded_children()
+    }
+
+    /// If this node has unloaded children, asks the tree's load handler
+    /// to load them; see [`Tree::set_load_handler`](crate::Tree::set_load_handler).
+    /// Adapters should call this when an assistive technology navigates
+    /// into the node. The child iterators never call it themselves.
+    pub fn request_children(&self) {
+        if self.has_unloaded_children() {
+            self.tree_state.request_children(self.id);
+        }
+    }
+
     pub fn child_ids(
         &self,
     ) -> impl DoubleEndedIterator<Item = NodeId>
@@ -122,6 +305,16 @@ impl<'a> Node<'a> {
         FilteredChildren::new(*self, filter)
     }
 
+    /// Returns the children that [`common_filter`] includes, descending
+    /// through excluded (ignored) children to their included descendants.
+    /// This is the downward counterpart of calling
+    /// [`Node::filtered_parent`] with the same filter.
+    pub fn unignored_children(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + FusedIterator<Item = Node<'a>> + 'a {
+        self.filtered_children(common_filter)
+    }
+
     pub fn following_sibling_ids(
         &self,
     ) -> impl DoubleEndedIterator<Item = NodeId>
@@ -224,6 +417,56 @@ impl<'a> Node<'a> {
         false
     }
 
+    fn is_exposed(&self, filter: &impl Fn(&Node) -> FilterResult) -> bool {
+        if filter(self) != FilterResult::Include {
+            return false;
+        }
+        let mut current = self.parent();
+        while let Some(ancestor) = current {
+            if filter(&ancestor) == FilterResult::ExcludeSubtree {
+                return false;
+            }
+            current = ancestor.parent();
+        }
+        true
+    }
+
+    /// Returns the first node after this one in document order that has
+    /// the given role and is included by the filter. This uses the tree's
+    /// role index rather than walking every node that follows this one.
+    pub fn next_with_role(
+        &self,
+        role: Role,
+        filter: &impl Fn(&Node) -> FilterResult,
+    ) -> Option<Node<'a>> {
+        let state = self.tree_state;
+        let ids = state.role_ids_in_document_order(role);
+        let path = self.index_path();
+        let start = ids.partition_point(|id| state.node_by_id(*id).unwrap().index_path() <= path);
+        ids[start..]
+            .iter()
+            .map(|id| state.node_by_id(*id).unwrap())
+            .find(|node| node.is_exposed(filter))
+    }
+
+    /// Returns the last node before this one in document order that has
+    /// the given role and is included by the filter.
+    pub fn previous_with_role(
+        &self,
+        role: Role,
+        filter: &impl Fn(&Node) -> FilterResult,
+    ) -> Option<Node<'a>> {
+        let state = self.tree_state;
+        let ids = state.role_ids_in_document_order(role);
+        let path = self.index_path();
+        let end = ids.partition_point(|id| state.node_by_id(*id).unwrap().index_path() < path);
+        ids[..end]
+            .iter()
+            .rev()
+            .map(|id| state.node_by_id(*id).unwrap())
+            .find(|node| node.is_exposed(filter))
+    }
+
     /// Returns the transform defined directly on this node, or the identity
     /// transform, without taking into account transforms on ancestors.
     pub fn direct_transform(&self) -> Affine {
@@ -240,6 +483,19 @@ impl<'a> Node<'a> {
             * self.direct_transform()
     }
 
+    /// Returns the transform from the root's coordinate space to this
+    /// node's local coordinate space, i.e. the inverse of [`Node::transform`].
+    pub fn transform_from_root(&self) -> Affine {
+        self.transform().inverse()
+    }
+
+    /// Converts a point in the root's coordinate space to this node's
+    /// local coordinate space, the space in which its raw bounds
+    /// are expressed.
+    pub fn point_to_node_local(&self, point: Point) -> Point {
+        self.transform_from_root() * point
+    }
+
     pub(crate) fn relative_transform(&self, stop_at: &Node) -> Affine {
         let parent_transform = if let Some(parent) = self.parent() {
             if parent.id() == stop_at.id() {
@@ -269,12 +525,68 @@ impl<'a> Node<'a> {
             .map(|rect| self.transform().transform_rect_bbox(*rect))
     }
 
+    /// Returns the node's transformed bounding box in screen coordinates,
+    /// using the tree's [root-to-screen transform](TreeState::root_to_screen_transform).
+    pub fn bounding_box_in_screen_space(&self) -> Option<Rect> {
+        self.raw_bounds().as_ref().map(|rect| {
+            (self.tree_state.root_to_screen_transform() * self.transform())
+                .transform_rect_bbox(*rect)
+        })
+    }
+
     pub(crate) fn bounding_box_in_coordinate_space(&self, other: &Node) -> Option<Rect> {
         self.raw_bounds()
             .as_ref()
             .map(|rect| self.relative_transform(other).transform_rect_bbox(*rect))
     }
 
+    /// Returns the stacking order of this node among its siblings.
+    /// See [`Node::children_in_paint_order`].
+    pub fn z_index(&self) -> usize {
+        self.data().z_index().unwrap_or(0)
+    }
+
+    pub(crate) fn paint_ordered_children(&self) -> Vec<Node<'a>> {
+        let mut children = self.children().collect::<Vec<_>>();
+        // The sort is stable, so siblings with the same z-index
+        // stay in document order.
+        children.sort_by_key(|child| child.z_index());
+        children
+    }
+
+    /// Returns the children that the filter includes in paint order,
+    /// that is, from the one drawn first (at the back) to the one drawn
+    /// last (on top). Children are ordered by their [z-index](Node::z_index),
+    /// and children with the same z-index are in document order. Each node
+    /// is drawn before its descendants, and the descendants of an excluded
+    /// child take the child's place in the order, so a z-index only
+    /// affects the order of a node relative to its siblings.
+    pub fn children_in_paint_order(
+        &self,
+        filter: &impl Fn(&Node) -> FilterResult,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>>
+           + ExactSizeIterator<Item = Node<'a>>
+           + FusedIterator<Item = Node<'a>>
+           + 'a {
+        let mut result = Vec::new();
+        self.add_children_in_paint_order(filter, &mut result);
+        result.into_iter()
+    }
+
+    fn add_children_in_paint_order(
+        &self,
+        filter: &impl Fn(&Node) -> FilterResult,
+        result: &mut Vec<Node<'a>>,
+    ) {
+        for child in self.paint_ordered_children() {
+            match apply_filter(filter, &child) {
+                FilterResult::Include => result.push(child),
+                FilterResult::ExcludeNode => child.add_children_in_paint_order(filter, result),
+                FilterResult::ExcludeSubtree => (),
+            }
+        }
+    }
+
     pub(crate) fn hit_test(
         &self,
         point: Point,
@@ -286,7 +598,7 @@ impl<'a> Node<'a> {
             return None;
         }
 
-        for child in self.children().rev() {
+        for child in self.paint_ordered_children().into_iter().rev() {
             let point = child.direct_transform().inverse() * point;
             if let Some(result) = child.hit_test(point, filter) {
                 return Some(result);
@@ -305,7 +617,9 @@ impl<'a> Node<'a> {
     }
 
     /// Returns the deepest filtered node, either this node or a descendant,
-    /// at the given point in this node's coordinate space.
+    /// at the given point in this node's coordinate space. Where nodes
+    /// overlap, the one on top in [paint order](Node::children_in_paint_order)
+    /// wins.
     pub fn node_at_point(
         &self,
         point: Point,
@@ -351,6 +665,21 @@ impl<'a> Node<'a> {
         self.is_read_only() || self.is_disabled()
     }
 
+    pub fn is_busy(&self) -> bool {
+        self.data().is_busy()
+    }
+
+    /// Returns true if this node or any of its ancestors is busy.
+    /// Unlike most ancestor queries, this doesn't take a filter, because
+    /// a busy container's contents are incomplete even if the container
+    /// itself isn't exposed to assistive technologies.
+    pub fn is_within_busy_subtree(&self) -> bool {
+        self.is_busy()
+            || self
+                .parent()
+                .is_some_and(|parent| parent.is_within_busy_subtree())
+    }
+
     pub fn toggled(&self) -> Option<Toggled> {
         self.data().toggled()
     }
@@ -375,6 +704,86 @@ impl<'a> Node<'a> {
         self.data().numeric_value_jump()
     }
 
+    /// Returns the step used when incrementing or decrementing this node's
+    /// numeric value. If the provider didn't set a positive step, this falls
+    /// back to one tenth of the range, like most platforms do.
+    fn effective_numeric_value_step(&self) -> Option<f64> {
+        self.numeric_value_step()
+            .filter(|step| *step > 0.0)
+            .or_else(|| {
+                let min = self.min_numeric_value()?;
+                let max = self.max_numeric_value()?;
+                (max > min).then(|| (max - min) / 10.0)
+            })
+    }
+
+    fn stepped_numeric_value(&self, direction: f64) -> Option<f64> {
+        let value = self.numeric_value()?;
+        let step = self.effective_numeric_value_step()?;
+        let mut result = value + direction * step;
+        if let Some(max) = self.max_numeric_value() {
+            result = result.min(max);
+        }
+        if let Some(min) = self.min_numeric_value() {
+            result = result.max(min);
+        }
+        Some(result)
+    }
+
+    /// Returns the value this node would have after one increment,
+    /// clamped to the maximum.
+    pub fn next_numeric_value(&self) -> Option<f64> {
+        self.stepped_numeric_value(1.0)
+    }
+
+    /// Returns the value this node would have after one decrement,
+    /// clamped to the minimum.
+    pub fn previous_numeric_value(&self) -> Option<f64> {
+        self.stepped_numeric_value(-1.0)
+    }
+
+    pub fn scroll_x(&self) -> Option<f64> {
+        self.data().scroll_x()
+    }
+
+    pub fn scroll_x_min(&self) -> Option<f64> {
+        self.data().scroll_x_min()
+    }
+
+    pub fn scroll_x_max(&self) -> Option<f64> {
+        self.data().scroll_x_max()
+    }
+
+    pub fn scroll_y(&self) -> Option<f64> {
+        self.data().scroll_y()
+    }
+
+    pub fn scroll_y_min(&self) -> Option<f64> {
+        self.data().scroll_y_min()
+    }
+
+    pub fn scroll_y_max(&self) -> Option<f64> {
+        self.data().scroll_y_max()
+    }
+
+    /// Returns whether the provider has declared a horizontal scroll range,
+    /// with a maximum greater than the minimum (which defaults to zero).
+    pub fn is_horizontally_scrollable(&self) -> bool {
+        self.scroll_x_max()
+            .is_some_and(|max| max > self.scroll_x_min().unwrap_or(0.0))
+    }
+
+    /// Returns whether the provider has declared a vertical scroll range,
+    /// with a maximum greater than the minimum (which defaults to zero).
+    pub fn is_vertically_scrollable(&self) -> bool {
+        self.scroll_y_max()
+            .is_some_and(|max| max > self.scroll_y_min().unwrap_or(0.0))
+    }
+
+    pub fn is_scrollable(&self) -> bool {
+        self.is_horizontally_scrollable() || self.is_vertically_scrollable()
+    }
+
     pub fn is_text_input(&self) -> bool {
         matches!(
             self.role(),
@@ -396,6 +805,13 @@ impl<'a> Node<'a> {
         )
     }
 
+    /// Returns true if the node's value should not be revealed,
+    /// either because the node is explicitly marked as protected
+    /// or because it's a password input.
+    pub fn is_protected(&self) -> bool {
+        self.data().is_protected() || self.role() == Role::PasswordInput
+    }
+
     pub fn is_multiline(&self) -> bool {
         self.role() == Role::MultilineTextInput
     }
@@ -408,6 +824,57 @@ impl<'a> Node<'a> {
         self.data().default_action_verb()
     }
 
+    /// Returns the verb that best describes what the default action does,
+    /// for use in hints such as "double tap to activate". A verb that
+    /// the provider set explicitly takes precedence; otherwise, the verb
+    /// is derived from the role, the checked and expanded states,
+    /// and the actions the node supports. Returns `None` if the node
+    /// has no default action.
+    pub fn action_verb(&self) -> Option<ActionVerb> {
+        match self.default_action_verb() {
+            Some(DefaultActionVerb::Check) => return Some(ActionVerb::Check),
+            Some(DefaultActionVerb::Uncheck) => return Some(ActionVerb::Uncheck),
+            Some(DefaultActionVerb::Jump) => return Some(ActionVerb::Jump),
+            Some(DefaultActionVerb::Open) => return Some(ActionVerb::Open),
+            _ => (),
+        }
+        let has_default_action = self.is_clickable() || self.supports_action(Action::Default);
+        if let Some(expanded) = self.is_expanded() {
+            let (action, verb) = if expanded {
+                (Action::Collapse, ActionVerb::Collapse)
+            } else {
+                (Action::Expand, ActionVerb::Expand)
+            };
+            if has_default_action || self.supports_action(action) {
+                return Some(verb);
+            }
+        }
+        if self.numeric_value().is_some()
+            && (self.supports_action(Action::Increment)
+                || self.supports_action(Action::Decrement)
+                || self.supports_action(Action::SetValue))
+        {
+            return Some(ActionVerb::Adjust);
+        }
+        if !has_default_action {
+            return None;
+        }
+        if let Some(toggled) = self.toggled() {
+            return Some(match (self.role(), toggled) {
+                (Role::Switch | Role::Button, _) => ActionVerb::Toggle,
+                (_, Toggled::True) => ActionVerb::Uncheck,
+                _ => ActionVerb::Check,
+            });
+        }
+        if self.role() == Role::Link {
+            return Some(ActionVerb::Jump);
+        }
+        if self.data().has_popup().is_some() {
+            return Some(ActionVerb::Open);
+        }
+        Some(ActionVerb::Click)
+    }
+
     // When probing for supported actions as the next several functions do,
     // it's tempting to check the role. But it's better to not assume anything
     // beyond what the provider has explicitly told us. Rationale:
@@ -435,8 +902,12 @@ impl<'a> Node<'a> {
         self.toggled().is_some()
     }
 
+    pub fn is_expanded(&self) -> Option<bool> {
+        self.data().is_expanded()
+    }
+
     pub fn supports_expand_collapse(&self) -> bool {
-        self.data().is_expanded().is_some()
+        self.is_expanded().is_some()
     }
 
     pub fn is_invocable(&self) -> bool {
@@ -474,6 +945,28 @@ impl<'a> Node<'a> {
     pub fn supports_decrement(&self) -> bool {
         self.supports_action(Action::Decrement)
     }
+
+    /// Returns the node's custom actions as `(id, description)` pairs,
+    /// in the order the provider specified them. Providers are expected
+    /// to list the most important actions first.
+    pub fn custom_actions(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (i32, &'a str)>
+           + ExactSizeIterator<Item = (i32, &'a str)>
+           + FusedIterator<Item = (i32, &'a str)>
+           + 'a {
+        self.state
+            .data
+            .custom_actions()
+            .iter()
+            .map(|action| (action.id, &*action.description))
+    }
+
+    pub fn custom_action_description(&self, id: i32) -> Option<&'a str> {
+        self.custom_actions()
+            .find(|(action_id, _)| *action_id == id)
+            .map(|(_, description)| description)
+    }
 }
 
 fn descendant_label_filter(node: &Node) -> FilterResult {
@@ -511,6 +1004,75 @@ impl<'a> Node<'a> {
         }
     }
 
+    /// Returns the nodes that this node is a label for, i.e. the nodes
+    /// that refer to this node through their `labelled_by` property.
+    pub fn labels(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>>
+           + ExactSizeIterator<Item = Node<'a>>
+           + FusedIterator<Item = Node<'a>>
+           + 'a {
+        let state = self.tree_state;
+        state
+            .reverse_relations(self.id)
+            .labelled_by
+            .iter()
+            .map(move |id| state.node_by_id(*id).unwrap())
+    }
+
+    /// Returns true if this node is a label for at least one node
+    /// other than itself. Filters may use this to exclude labels
+    /// whose text is already exposed as the name of another node.
+    pub fn is_label_for_other_node(&self) -> bool {
+        self.labels().any(|node| node.id() != self.id())
+    }
+
+    /// Returns the nodes that this node controls.
+    pub fn controls(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>>
+           + ExactSizeIterator<Item = Node<'a>>
+           + FusedIterator<Item = Node<'a>>
+           + 'a {
+        let state = self.tree_state;
+        let data = &self.state.data;
+        data.controls()
+            .iter()
+            .map(move |id| state.node_by_id(*id).unwrap())
+    }
+
+    /// Returns the nodes that control this node, i.e. the nodes that refer
+    /// to this node through their `controls` property.
+    pub fn controlled_by(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>>
+           + ExactSizeIterator<Item = Node<'a>>
+           + FusedIterator<Item = Node<'a>>
+           + 'a {
+        let state = self.tree_state;
+        state
+            .reverse_relations(self.id)
+            .controls
+            .iter()
+            .map(move |id| state.node_by_id(*id).unwrap())
+    }
+
+    /// Returns the nodes that this node describes, i.e. the nodes that
+    /// refer to this node through their `described_by` property.
+    pub fn described_by_reverse(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>>
+           + ExactSizeIterator<Item = Node<'a>>
+           + FusedIterator<Item = Node<'a>>
+           + 'a {
+        let state = self.tree_state;
+        state
+            .reverse_relations(self.id)
+            .described_by
+            .iter()
+            .map(move |id| state.node_by_id(*id).unwrap())
+    }
+
     pub fn name(&self) -> Option<String> {
         if let Some(name) = &self.data().name() {
             Some(name.to_string())
@@ -545,6 +1107,16 @@ impl<'a> Node<'a> {
         }
     }
 
+    /// Returns the text that should be announced for this node's value:
+    /// the provider's value text if present, otherwise the formatted
+    /// numeric value.
+    pub fn value_text_or_formatted(&self) -> Option<String> {
+        self.data()
+            .value()
+            .map(|value| value.to_string())
+            .or_else(|| self.numeric_value().map(|value| value.to_string()))
+    }
+
     pub fn has_value(&self) -> bool {
         self.data().value().is_some() || (self.supports_text_ranges() && !self.is_multiline())
     }
@@ -592,6 +1164,10 @@ impl<'a> Node<'a> {
         )
     }
 
+    pub fn invalid(&self) -> Option<Invalid> {
+        self.data().invalid()
+    }
+
     pub fn live(&self) -> Live {
         self.data()
             .live()
@@ -602,6 +1178,118 @@ impl<'a> Node<'a> {
         self.data().is_selected()
     }
 
+    pub fn is_multiselectable(&self) -> bool {
+        self.data().is_multiselectable()
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.data().is_required()
+    }
+
+    pub fn aria_current(&self) -> Option<AriaCurrent> {
+        self.data().aria_current()
+    }
+
+    /// Returns true if this node is the current item within a container
+    /// or set of related items, such as the current page in a navigation
+    /// menu or the current step in a process.
+    ///
+    /// If the provider set [`Node::aria_current`], that is used as-is.
+    /// Otherwise, the only inference made is that the selected tab
+    /// of a single-selection tab list is current, and only if it's
+    /// the only selected tab in that list.
+    pub fn is_current(&self) -> bool {
+        if let Some(current) = self.aria_current() {
+            return current != AriaCurrent::False;
+        }
+        if self.role() != Role::Tab || self.is_selected() != Some(true) {
+            return false;
+        }
+        let Some(parent) = self.parent() else {
+            return false;
+        };
+        parent.role() == Role::TabList
+            && !parent.is_multiselectable()
+            && parent
+                .children()
+                .filter(|child| child.role() == Role::Tab && child.is_selected() == Some(true))
+                .count()
+                == 1
+    }
+
+    /// Returns the descendant of this node that is the current item,
+    /// as defined by [`Node::is_current`], considering only descendants
+    /// that the filter doesn't exclude. Returns `None` if there is no
+    /// such descendant, or if there is more than one.
+    pub fn current_descendant(&self, filter: &impl Fn(&Node) -> FilterResult) -> Option<Node<'a>> {
+        let mut result = None;
+        let mut stack = self.children().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            match filter(&node) {
+                FilterResult::Include if node.is_current() => {
+                    if result.is_some() {
+                        return None;
+                    }
+                    result = Some(node);
+                }
+                FilterResult::ExcludeSubtree => continue,
+                _ => (),
+            }
+            stack.extend(node.children());
+        }
+        result
+    }
+
+    pub fn is_container_with_selectable_children(&self) -> bool {
+        matches!(
+            self.role(),
+            Role::ComboBox
+                | Role::EditableComboBox
+                | Role::Grid
+                | Role::ListBox
+                | Role::ListGrid
+                | Role::Menu
+                | Role::MenuBar
+                | Role::MenuListPopup
+                | Role::RadioGroup
+                | Role::TabList
+                | Role::Toolbar
+                | Role::Tree
+                | Role::TreeGrid
+        )
+    }
+
+    /// Returns the nearest filtered ancestor whose children can be selected.
+    pub fn selection_container(&self, filter: &impl Fn(&Node) -> FilterResult) -> Option<Node<'a>> {
+        let mut current = self.filtered_parent(filter);
+        while let Some(ancestor) = current {
+            if ancestor.is_container_with_selectable_children() {
+                return Some(ancestor);
+            }
+            current = ancestor.filtered_parent(filter);
+        }
+        None
+    }
+
+    /// Returns the filtered descendants whose
+    /// [selection container](Node::selection_container) is this node,
+    /// in document order. Nested selection containers are included,
+    /// but not their descendants, which belong to the nested container.
+    pub fn selection_container_descendants(
+        &self,
+        filter: impl Fn(&Node) -> FilterResult + Copy + 'a,
+    ) -> Vec<Node<'a>> {
+        let mut result = Vec::new();
+        let mut stack: Vec<Node<'a>> = self.filtered_children(filter).rev().collect();
+        while let Some(node) = stack.pop() {
+            result.push(node);
+            if !node.is_container_with_selectable_children() {
+                stack.extend(node.filtered_children(filter).rev());
+            }
+        }
+        result
+    }
+
     pub fn raw_text_selection(&self) -> Option<&TextSelection> {
         self.data().text_selection()
     }
@@ -639,7 +1327,7 @@ impl<'a> Node<'a> {
         filter: &impl Fn(&Node) -> FilterResult,
     ) -> Option<Node<'a>> {
         for child in self.children() {
-            let result = filter(&child);
+            let result = apply_filter(filter, &child);
             if result == FilterResult::Include {
                 return Some(child);
             }
@@ -657,7 +1345,7 @@ impl<'a> Node<'a> {
         filter: &impl Fn(&Node) -> FilterResult,
     ) -> Option<Node<'a>> {
         for child in self.children().rev() {
-            let result = filter(&child);
+            let result = apply_filter(filter, &child);
             if result == FilterResult::Include {
                 return Some(child);
             }
@@ -673,7 +1361,7 @@ impl<'a> Node<'a> {
 
 #[cfg(test)]
 mod tests {
-    use accesskit::{NodeBuilder, NodeId, Point, Rect, Role, Tree, TreeUpdate};
+    use accesskit::{NodeBuilder, NodeId, Point, Rect, Role, TreeUpdateBuilder};
 
     use crate::tests::*;
 
@@ -732,12 +1420,116 @@ mod tests {
     }
 
     #[test]
-    fn filtered_parent() {
-        let tree = test_tree();
+    fn unignored_children() {
+        const ROOT_ID: NodeId = NodeId(0);
+        const OUTER_CONTAINER_ID: NodeId = NodeId(1);
+        const LABEL_1_0_ID: NodeId = NodeId(2);
+        const INNER_CONTAINER_ID: NodeId = NodeId(3);
+        const BUTTON_1_1_0_ID: NodeId = NodeId(4);
+        const HIDDEN_LABEL_ID: NodeId = NodeId(5);
+        const LABEL_2_ID: NodeId = NodeId(6);
+
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![OUTER_CONTAINER_ID, HIDDEN_LABEL_ID, LABEL_2_ID]);
+                builder.build()
+            })
+            .node(OUTER_CONTAINER_ID, {
+                let mut builder = NodeBuilder::new(Role::GenericContainer);
+                builder.set_children(vec![LABEL_1_0_ID, INNER_CONTAINER_ID]);
+                builder.build()
+            })
+            .node(LABEL_1_0_ID, NodeBuilder::new(Role::Label).build())
+            .node(INNER_CONTAINER_ID, {
+                let mut builder = NodeBuilder::new(Role::GenericContainer);
+                builder.set_children(vec![BUTTON_1_1_0_ID]);
+                builder.build()
+            })
+            .node(BUTTON_1_1_0_ID, NodeBuilder::new(Role::Button).build())
+            .node(HIDDEN_LABEL_ID, {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_hidden();
+                builder.build()
+            })
+            .node(LABEL_2_ID, NodeBuilder::new(Role::Label).build())
+            .focus(ROOT_ID)
+            .build();
+        let tree = crate::Tree::new(update, false);
+        let root = tree.state().root();
+
+        fn manual(node: crate::Node, result: &mut Vec<NodeId>) {
+            for child in node.children() {
+                match crate::common_filter(&child) {
+                    crate::FilterResult::Include => result.push(child.id()),
+                    crate::FilterResult::ExcludeNode => manual(child, result),
+                    crate::FilterResult::ExcludeSubtree => (),
+                }
+            }
+        }
+        let mut expected = Vec::new();
+        manual(root, &mut expected);
+        assert_eq!(vec![LABEL_1_0_ID, BUTTON_1_1_0_ID, LABEL_2_ID], expected);
         assert_eq!(
-            ROOT_ID,
+            expected,
+            root.unignored_children()
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()
+        );
+        assert_eq!(
+            vec![LABEL_2_ID, BUTTON_1_1_0_ID, LABEL_1_0_ID],
+            root.unignored_children()
+                .rev()
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()
+        );
+        assert_eq!(
+            vec![BUTTON_1_1_0_ID],
             tree.state()
-                .node_by_id(LABEL_1_1_ID)
+                .node_by_id(INNER_CONTAINER_ID)
+                .unwrap()
+                .unignored_children()
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()
+        );
+    }
+
+    #[test]
+    fn filtered_parent() {
+        let tree = test_tree();
+        assert_eq!(
+            ROOT_ID,
+            tree.state()
+                .node_by_id(LABEL_1_1_ID)
+                .unwrap()
+                .filtered_parent(&test_tree_filter)
+                .unwrap()
+                .id()
+        );
+        // Skips both ignored and hidden ancestors.
+        assert_eq!(
+            ROOT_ID,
+            tree.state()
+                .node_by_id(CONTAINER_1_0_0_HIDDEN_ID)
+                .unwrap()
+                .filtered_parent(&test_tree_filter)
+                .unwrap()
+                .id()
+        );
+        assert_eq!(
+            ROOT_ID,
+            tree.state()
+                .node_by_id(LABEL_3_1_0_ID)
+                .unwrap()
+                .filtered_parent(&test_tree_filter)
+                .unwrap()
+                .id()
+        );
+        assert_eq!(
+            PARAGRAPH_2_ID,
+            tree.state()
+                .node_by_id(LABEL_2_0_ID)
                 .unwrap()
                 .filtered_parent(&test_tree_filter)
                 .unwrap()
@@ -750,6 +1542,38 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn filtered_ancestor_of_role() {
+        let tree = test_tree();
+        let state = tree.state();
+        assert_eq!(
+            Some(PARAGRAPH_2_ID),
+            state
+                .node_by_id(LABEL_2_0_ID)
+                .unwrap()
+                .filtered_ancestor_of_role(Role::Paragraph, &test_tree_filter)
+                .map(|node| node.id())
+        );
+        // The label's paragraph is ignored.
+        let label_3_1_0 = state.node_by_id(LABEL_3_1_0_ID).unwrap();
+        assert!(label_3_1_0
+            .filtered_ancestor_of_role(Role::Paragraph, &test_tree_filter)
+            .is_none());
+        assert!(label_3_1_0
+            .filtered_ancestor_of_role(Role::Link, &test_tree_filter)
+            .is_none());
+        assert_eq!(
+            Some(ROOT_ID),
+            label_3_1_0
+                .filtered_ancestor_of_role(Role::RootWebArea, &test_tree_filter)
+                .map(|node| node.id())
+        );
+        assert!(state
+            .root()
+            .filtered_ancestor_of_role(Role::RootWebArea, &test_tree_filter)
+            .is_none());
+    }
+
     #[test]
     fn deepest_first_filtered_child() {
         let tree = test_tree();
@@ -868,6 +1692,12 @@ mod tests {
         let tree = test_tree();
         assert!(tree.state().node_by_id(ROOT_ID).unwrap().is_root());
         assert!(!tree.state().node_by_id(PARAGRAPH_0_ID).unwrap().is_root());
+        assert_eq!(ROOT_ID, tree.state().root().id());
+        let root_count = (ROOT_ID.0..=EMPTY_CONTAINER_3_3_IGNORED_ID.0)
+            .map(|id| tree.state().node_by_id(NodeId(id)).unwrap())
+            .filter(|node| node.is_root())
+            .count();
+        assert_eq!(1, root_count);
     }
 
     #[test]
@@ -905,6 +1735,130 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bounding_box_with_transforms() {
+        use accesskit::{Affine, Vec2};
+
+        const ROOT_ID: NodeId = NodeId(0);
+        const CONTAINER_ID: NodeId = NodeId(1);
+        const CHILD_ID: NodeId = NodeId(2);
+
+        let bounds = Rect {
+            x0: 0.0,
+            y0: 0.0,
+            x1: 10.0,
+            y1: 20.0,
+        };
+        let build_tree = |container_transform: Affine, child_transform: Affine| {
+            let root = {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![CONTAINER_ID]);
+                builder.build()
+            };
+            let container = {
+                let mut builder = NodeBuilder::new(Role::GenericContainer);
+                builder.set_transform(container_transform);
+                builder.set_children(vec![CHILD_ID]);
+                builder.build()
+            };
+            let child = {
+                let mut builder = NodeBuilder::new(Role::Button);
+                builder.set_transform(child_transform);
+                builder.set_bounds(bounds);
+                builder.build()
+            };
+            let update = TreeUpdateBuilder::new()
+                .tree_root(ROOT_ID)
+                .node(ROOT_ID, root)
+                .node(CONTAINER_ID, container)
+                .node(CHILD_ID, child)
+                .build();
+            crate::Tree::new(update, false)
+        };
+        let child_bounds = |tree: &crate::Tree| {
+            tree.state()
+                .node_by_id(CHILD_ID)
+                .unwrap()
+                .bounding_box()
+                .unwrap()
+        };
+
+        // A rotation by 90 degrees, written out exactly to avoid rounding.
+        let rotate_90 = Affine::new([0.0, 1.0, -1.0, 0.0, 0.0, 0.0]);
+        let tree = build_tree(Affine::IDENTITY, rotate_90);
+        assert_eq!(Rect::new(-20.0, 0.0, 0.0, 10.0), child_bounds(&tree));
+
+        let tree = build_tree(Affine::IDENTITY, Affine::scale(2.0));
+        assert_eq!(Rect::new(0.0, 0.0, 20.0, 40.0), child_bounds(&tree));
+
+        let tree = build_tree(Affine::IDENTITY, Affine::scale_non_uniform(3.0, 0.5));
+        assert_eq!(Rect::new(0.0, 0.0, 30.0, 10.0), child_bounds(&tree));
+
+        let tree = build_tree(Affine::IDENTITY, Affine::translate(Vec2::new(5.0, -5.0)));
+        assert_eq!(Rect::new(5.0, -5.0, 15.0, 15.0), child_bounds(&tree));
+
+        // Transforms on ancestors are applied after the node's own transform.
+        let tree = build_tree(
+            Affine::translate(Vec2::new(100.0, 50.0)),
+            rotate_90 * Affine::scale(2.0),
+        );
+        assert_eq!(Rect::new(60.0, 50.0, 100.0, 70.0), child_bounds(&tree));
+    }
+
+    #[test]
+    fn coordinate_space_conversions() {
+        use accesskit::{Affine, Vec2};
+
+        const ROOT_ID: NodeId = NodeId(0);
+        const BUTTON_ID: NodeId = NodeId(1);
+
+        let root = {
+            let mut builder = NodeBuilder::new(Role::Window);
+            builder.set_children(vec![BUTTON_ID]);
+            builder.build()
+        };
+        let button = {
+            let mut builder = NodeBuilder::new(Role::Button);
+            // A rotation by 90 degrees, written out exactly to avoid rounding.
+            builder.set_transform(
+                Affine::translate(Vec2::new(50.0, 10.0))
+                    * Affine::new([0.0, 1.0, -1.0, 0.0, 0.0, 0.0]),
+            );
+            builder.set_bounds(Rect::new(0.0, 0.0, 20.0, 10.0));
+            builder.build()
+        };
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, root)
+            .node(BUTTON_ID, button)
+            .build();
+        let mut tree = crate::Tree::new(update, false);
+        assert_eq!(Affine::IDENTITY, tree.state().root_to_screen_transform());
+        tree.set_root_to_screen_transform(
+            Affine::translate(Vec2::new(100.0, 200.0)) * Affine::scale(2.0),
+        );
+
+        let button = tree.state().node_by_id(BUTTON_ID).unwrap();
+        assert_eq!(
+            Some(Rect::new(40.0, 10.0, 50.0, 30.0)),
+            button.bounding_box()
+        );
+        assert_eq!(
+            Some(Rect::new(180.0, 220.0, 200.0, 260.0)),
+            button.bounding_box_in_screen_space()
+        );
+        assert_eq!(None, tree.state().root().bounding_box_in_screen_space());
+
+        let point_in_root = Point::new(45.0, 12.0);
+        let local = button.point_to_node_local(point_in_root);
+        assert_eq!(Point::new(2.0, 5.0), local);
+        assert_eq!(point_in_root, button.transform() * local);
+        assert_eq!(
+            button.transform_from_root() * button.transform(),
+            Affine::IDENTITY
+        );
+    }
+
     #[test]
     fn node_at_point() {
         let tree = test_tree();
@@ -935,19 +1889,163 @@ mod tests {
     }
 
     #[test]
-    fn no_name_or_labelled_by() {
-        let update = TreeUpdate {
-            nodes: vec![
-                (NodeId(0), {
-                    let mut builder = NodeBuilder::new(Role::Window);
-                    builder.set_children(vec![NodeId(1)]);
-                    builder.build()
-                }),
-                (NodeId(1), NodeBuilder::new(Role::Button).build()),
+    fn paint_order() {
+        const ROOT_ID: NodeId = NodeId(0);
+        const DIALOG_ID: NodeId = NodeId(1);
+        const DIALOG_BUTTON_ID: NodeId = NodeId(2);
+        const PAGE_ID: NodeId = NodeId(3);
+        const COMBO_BOX_ID: NodeId = NodeId(4);
+        const LIST_BOX_ID: NodeId = NodeId(5);
+        const OPTION_ID: NodeId = NodeId(6);
+        const BUTTON_ID: NodeId = NodeId(7);
+        const LABEL_ID: NodeId = NodeId(8);
+
+        let node = |role, bounds: Option<Rect>, children: Vec<NodeId>, z_index| {
+            let mut builder = NodeBuilder::new(role);
+            if let Some(bounds) = bounds {
+                builder.set_bounds(bounds);
+            }
+            builder.set_children(children);
+            if let Some(z_index) = z_index {
+                builder.set_z_index(z_index);
+            }
+            builder.build()
+        };
+        let rect = |x0, y0, x1, y1| Some(Rect { x0, y0, x1, y1 });
+        // The modal dialog comes first in document order, and the
+        // combo box's popup extends over the button that follows it,
+        // but both are raised above the rest of the page.
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(
+                ROOT_ID,
+                node(
+                    Role::Window,
+                    rect(0.0, 0.0, 400.0, 400.0),
+                    vec![DIALOG_ID, PAGE_ID],
+                    None,
+                ),
+            )
+            .node(
+                DIALOG_ID,
+                node(
+                    Role::Dialog,
+                    rect(100.0, 100.0, 300.0, 300.0),
+                    vec![DIALOG_BUTTON_ID],
+                    Some(1),
+                ),
+            )
+            .node(
+                DIALOG_BUTTON_ID,
+                node(Role::Button, rect(150.0, 150.0, 250.0, 200.0), vec![], None),
+            )
+            .node(
+                PAGE_ID,
+                node(
+                    Role::GenericContainer,
+                    None,
+                    vec![COMBO_BOX_ID, BUTTON_ID, LABEL_ID],
+                    None,
+                ),
+            )
+            .node(
+                COMBO_BOX_ID,
+                node(
+                    Role::ComboBox,
+                    rect(0.0, 0.0, 100.0, 20.0),
+                    vec![LIST_BOX_ID],
+                    Some(1),
+                ),
+            )
+            .node(
+                LIST_BOX_ID,
+                node(
+                    Role::ListBox,
+                    rect(0.0, 20.0, 100.0, 80.0),
+                    vec![OPTION_ID],
+                    None,
+                ),
+            )
+            .node(
+                OPTION_ID,
+                node(
+                    Role::ListBoxOption,
+                    rect(0.0, 20.0, 100.0, 40.0),
+                    vec![],
+                    None,
+                ),
+            )
+            .node(
+                BUTTON_ID,
+                node(Role::Button, rect(0.0, 60.0, 100.0, 100.0), vec![], None),
+            )
+            .node(
+                LABEL_ID,
+                node(Role::Label, rect(100.0, 100.0, 300.0, 120.0), vec![], None),
+            )
+            .build();
+        let tree = crate::Tree::new(update, false);
+        let state = tree.state();
+        let filter = crate::common_filter;
+
+        assert_eq!(
+            vec![BUTTON_ID, LABEL_ID, COMBO_BOX_ID, DIALOG_ID],
+            state
+                .root()
+                .children_in_paint_order(&filter)
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()
+        );
+        assert_eq!(
+            vec![DIALOG_ID, COMBO_BOX_ID, LABEL_ID, BUTTON_ID],
+            state
+                .root()
+                .children_in_paint_order(&filter)
+                .rev()
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()
+        );
+        assert_eq!(
+            vec![
+                ROOT_ID,
+                BUTTON_ID,
+                LABEL_ID,
+                COMBO_BOX_ID,
+                LIST_BOX_ID,
+                OPTION_ID,
+                DIALOG_ID,
+                DIALOG_BUTTON_ID
             ],
-            tree: Some(Tree::new(NodeId(0))),
-            focus: NodeId(0),
+            state
+                .nodes_in_paint_order(filter)
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()
+        );
+
+        let top_most = |x, y| {
+            state
+                .top_most_at_point(Point::new(x, y), &filter)
+                .map(|node| node.id())
         };
+        assert_eq!(Some(OPTION_ID), top_most(50.0, 35.0));
+        assert_eq!(Some(LIST_BOX_ID), top_most(50.0, 70.0));
+        assert_eq!(Some(BUTTON_ID), top_most(50.0, 90.0));
+        assert_eq!(Some(DIALOG_ID), top_most(150.0, 110.0));
+        assert_eq!(Some(DIALOG_BUTTON_ID), top_most(200.0, 175.0));
+        assert_eq!(Some(ROOT_ID), top_most(350.0, 350.0));
+    }
+
+    #[test]
+    fn no_name_or_labelled_by() {
+        let update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NodeId(1)]);
+                builder.build()
+            })
+            .node(NodeId(1), NodeBuilder::new(Role::Button).build())
+            .build();
         let tree = crate::Tree::new(update, false);
         assert_eq!(None, tree.state().node_by_id(NodeId(1)).unwrap().name());
     }
@@ -959,37 +2057,34 @@ mod tests {
         const LABEL_1: &str = "Check email every";
         const LABEL_2: &str = "minutes";
 
-        let update = TreeUpdate {
-            nodes: vec![
-                (NodeId(0), {
-                    let mut builder = NodeBuilder::new(Role::Window);
-                    builder.set_children(vec![NodeId(1), NodeId(2), NodeId(3), NodeId(4)]);
-                    builder.build()
-                }),
-                (NodeId(1), {
-                    let mut builder = NodeBuilder::new(Role::CheckBox);
-                    builder.set_labelled_by(vec![NodeId(2), NodeId(4)]);
-                    builder.build()
-                }),
-                (NodeId(2), {
-                    let mut builder = NodeBuilder::new(Role::Label);
-                    builder.set_name(LABEL_1);
-                    builder.build()
-                }),
-                (NodeId(3), {
-                    let mut builder = NodeBuilder::new(Role::TextInput);
-                    builder.push_labelled_by(NodeId(4));
-                    builder.build()
-                }),
-                (NodeId(4), {
-                    let mut builder = NodeBuilder::new(Role::Label);
-                    builder.set_name(LABEL_2);
-                    builder.build()
-                }),
-            ],
-            tree: Some(Tree::new(NodeId(0))),
-            focus: NodeId(0),
-        };
+        let update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NodeId(1), NodeId(2), NodeId(3), NodeId(4)]);
+                builder.build()
+            })
+            .node(NodeId(1), {
+                let mut builder = NodeBuilder::new(Role::CheckBox);
+                builder.set_labelled_by(vec![NodeId(2), NodeId(4)]);
+                builder.build()
+            })
+            .node(NodeId(2), {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_name(LABEL_1);
+                builder.build()
+            })
+            .node(NodeId(3), {
+                let mut builder = NodeBuilder::new(Role::TextInput);
+                builder.push_labelled_by(NodeId(4));
+                builder.build()
+            })
+            .node(NodeId(4), {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_name(LABEL_2);
+                builder.build()
+            })
+            .build();
         let tree = crate::Tree::new(update, false);
         assert_eq!(
             Some([LABEL_1, LABEL_2].join(" ")),
@@ -1001,6 +2096,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reverse_relations() {
+        const ROOT_ID: NodeId = NodeId(0);
+        const LABEL_ID: NodeId = NodeId(1);
+        const CHECKBOX_1_ID: NodeId = NodeId(2);
+        const CHECKBOX_2_ID: NodeId = NodeId(3);
+        const BUTTON_ID: NodeId = NodeId(4);
+
+        let root = {
+            let mut builder = NodeBuilder::new(Role::Window);
+            builder.set_children(vec![LABEL_ID, CHECKBOX_1_ID, CHECKBOX_2_ID, BUTTON_ID]);
+            builder.build()
+        };
+        let label = {
+            let mut builder = NodeBuilder::new(Role::Label);
+            builder.set_name("Label");
+            builder.build()
+        };
+        let labelled_checkbox = {
+            let mut builder = NodeBuilder::new(Role::CheckBox);
+            builder.push_labelled_by(LABEL_ID);
+            builder.push_described_by(LABEL_ID);
+            builder.build()
+        };
+        let unlabelled_checkbox = NodeBuilder::new(Role::CheckBox).build();
+        let button = {
+            let mut builder = NodeBuilder::new(Role::Button);
+            builder.push_controlled(CHECKBOX_1_ID);
+            builder.build()
+        };
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, root)
+            .node(LABEL_ID, label)
+            .node(CHECKBOX_1_ID, labelled_checkbox.clone())
+            .node(CHECKBOX_2_ID, unlabelled_checkbox.clone())
+            .node(BUTTON_ID, button)
+            .build();
+        let mut tree = crate::Tree::new(update, false);
+        let ids = |nodes: &mut dyn Iterator<Item = crate::Node>| -> Vec<NodeId> {
+            nodes.map(|node| node.id()).collect()
+        };
+        let state = tree.state();
+        let label = state.node_by_id(LABEL_ID).unwrap();
+        assert_eq!(vec![CHECKBOX_1_ID], ids(&mut label.labels()));
+        assert_eq!(vec![CHECKBOX_1_ID], ids(&mut label.described_by_reverse()));
+        assert!(label.is_label_for_other_node());
+        let checkbox_1 = state.node_by_id(CHECKBOX_1_ID).unwrap();
+        assert_eq!(vec![BUTTON_ID], ids(&mut checkbox_1.controlled_by()));
+        assert!(!checkbox_1.is_label_for_other_node());
+        let button = state.node_by_id(BUTTON_ID).unwrap();
+        assert_eq!(vec![CHECKBOX_1_ID], ids(&mut button.controls()));
+
+        tree.update(
+            TreeUpdateBuilder::new()
+                .node(CHECKBOX_1_ID, unlabelled_checkbox)
+                .node(CHECKBOX_2_ID, labelled_checkbox)
+                .focus(ROOT_ID)
+                .build(),
+        );
+        let state = tree.state();
+        let label = state.node_by_id(LABEL_ID).unwrap();
+        assert_eq!(vec![CHECKBOX_2_ID], ids(&mut label.labels()));
+        assert_eq!(vec![CHECKBOX_2_ID], ids(&mut label.described_by_reverse()));
+        assert!(label.is_label_for_other_node());
+
+        tree.update(
+            TreeUpdateBuilder::new()
+                .node(ROOT_ID, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![LABEL_ID, CHECKBOX_1_ID]);
+                    builder.build()
+                })
+                .focus(ROOT_ID)
+                .build(),
+        );
+        let state = tree.state();
+        let label = state.node_by_id(LABEL_ID).unwrap();
+        assert_eq!(0, label.labels().len());
+        assert_eq!(0, label.described_by_reverse().len());
+        assert!(!label.is_label_for_other_node());
+        let checkbox_1 = state.node_by_id(CHECKBOX_1_ID).unwrap();
+        assert_eq!(0, checkbox_1.controlled_by().len());
+    }
+
     #[test]
     fn name_from_descendant_label() {
         const ROOT_ID: NodeId = NodeId(0);
@@ -1032,114 +2212,111 @@ mod tests {
         const MENU_ITEM_CHECKBOX_LABEL: &str = "Apply volume processing";
         const MENU_ITEM_RADIO_LABEL: &str = "Maximize loudness for noisy environment";
 
-        let update = TreeUpdate {
-            nodes: vec![
-                (ROOT_ID, {
-                    let mut builder = NodeBuilder::new(Role::Window);
-                    builder.set_children(vec![
-                        DEFAULT_BUTTON_ID,
-                        LINK_ID,
-                        CHECKBOX_ID,
-                        RADIO_BUTTON_ID,
-                        MENU_BUTTON_ID,
-                        MENU_ID,
-                    ]);
-                    builder.build()
-                }),
-                (DEFAULT_BUTTON_ID, {
-                    let mut builder = NodeBuilder::new(Role::DefaultButton);
-                    builder.push_child(DEFAULT_BUTTON_LABEL_ID);
-                    builder.build()
-                }),
-                (DEFAULT_BUTTON_LABEL_ID, {
-                    let mut builder = NodeBuilder::new(Role::Image);
-                    builder.set_name(DEFAULT_BUTTON_LABEL);
-                    builder.build()
-                }),
-                (LINK_ID, {
-                    let mut builder = NodeBuilder::new(Role::Link);
-                    builder.push_child(LINK_LABEL_CONTAINER_ID);
-                    builder.build()
-                }),
-                (LINK_LABEL_CONTAINER_ID, {
-                    let mut builder = NodeBuilder::new(Role::GenericContainer);
-                    builder.push_child(LINK_LABEL_ID);
-                    builder.build()
-                }),
-                (LINK_LABEL_ID, {
-                    let mut builder = NodeBuilder::new(Role::Label);
-                    builder.set_name(LINK_LABEL);
-                    builder.build()
-                }),
-                (CHECKBOX_ID, {
-                    let mut builder = NodeBuilder::new(Role::CheckBox);
-                    builder.push_child(CHECKBOX_LABEL_ID);
-                    builder.build()
-                }),
-                (CHECKBOX_LABEL_ID, {
-                    let mut builder = NodeBuilder::new(Role::Label);
-                    builder.set_name(CHECKBOX_LABEL);
-                    builder.build()
-                }),
-                (RADIO_BUTTON_ID, {
-                    let mut builder = NodeBuilder::new(Role::RadioButton);
-                    builder.push_child(RADIO_BUTTON_LABEL_ID);
-                    builder.build()
-                }),
-                (RADIO_BUTTON_LABEL_ID, {
-                    let mut builder = NodeBuilder::new(Role::Label);
-                    builder.set_name(RADIO_BUTTON_LABEL);
-                    builder.build()
-                }),
-                (MENU_BUTTON_ID, {
-                    let mut builder = NodeBuilder::new(Role::Button);
-                    builder.push_child(MENU_BUTTON_LABEL_ID);
-                    builder.build()
-                }),
-                (MENU_BUTTON_LABEL_ID, {
-                    let mut builder = NodeBuilder::new(Role::Label);
-                    builder.set_name(MENU_BUTTON_LABEL);
-                    builder.build()
-                }),
-                (MENU_ID, {
-                    let mut builder = NodeBuilder::new(Role::Menu);
-                    builder.set_children([MENU_ITEM_ID, MENU_ITEM_CHECKBOX_ID, MENU_ITEM_RADIO_ID]);
-                    builder.build()
-                }),
-                (MENU_ITEM_ID, {
-                    let mut builder = NodeBuilder::new(Role::MenuItem);
-                    builder.push_child(MENU_ITEM_LABEL_ID);
-                    builder.build()
-                }),
-                (MENU_ITEM_LABEL_ID, {
-                    let mut builder = NodeBuilder::new(Role::Label);
-                    builder.set_name(MENU_ITEM_LABEL);
-                    builder.build()
-                }),
-                (MENU_ITEM_CHECKBOX_ID, {
-                    let mut builder = NodeBuilder::new(Role::MenuItemCheckBox);
-                    builder.push_child(MENU_ITEM_CHECKBOX_LABEL_ID);
-                    builder.build()
-                }),
-                (MENU_ITEM_CHECKBOX_LABEL_ID, {
-                    let mut builder = NodeBuilder::new(Role::Label);
-                    builder.set_name(MENU_ITEM_CHECKBOX_LABEL);
-                    builder.build()
-                }),
-                (MENU_ITEM_RADIO_ID, {
-                    let mut builder = NodeBuilder::new(Role::MenuItemRadio);
-                    builder.push_child(MENU_ITEM_RADIO_LABEL_ID);
-                    builder.build()
-                }),
-                (MENU_ITEM_RADIO_LABEL_ID, {
-                    let mut builder = NodeBuilder::new(Role::Label);
-                    builder.set_name(MENU_ITEM_RADIO_LABEL);
-                    builder.build()
-                }),
-            ],
-            tree: Some(Tree::new(ROOT_ID)),
-            focus: ROOT_ID,
-        };
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![
+                    DEFAULT_BUTTON_ID,
+                    LINK_ID,
+                    CHECKBOX_ID,
+                    RADIO_BUTTON_ID,
+                    MENU_BUTTON_ID,
+                    MENU_ID,
+                ]);
+                builder.build()
+            })
+            .node(DEFAULT_BUTTON_ID, {
+                let mut builder = NodeBuilder::new(Role::DefaultButton);
+                builder.push_child(DEFAULT_BUTTON_LABEL_ID);
+                builder.build()
+            })
+            .node(DEFAULT_BUTTON_LABEL_ID, {
+                let mut builder = NodeBuilder::new(Role::Image);
+                builder.set_name(DEFAULT_BUTTON_LABEL);
+                builder.build()
+            })
+            .node(LINK_ID, {
+                let mut builder = NodeBuilder::new(Role::Link);
+                builder.push_child(LINK_LABEL_CONTAINER_ID);
+                builder.build()
+            })
+            .node(LINK_LABEL_CONTAINER_ID, {
+                let mut builder = NodeBuilder::new(Role::GenericContainer);
+                builder.push_child(LINK_LABEL_ID);
+                builder.build()
+            })
+            .node(LINK_LABEL_ID, {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_name(LINK_LABEL);
+                builder.build()
+            })
+            .node(CHECKBOX_ID, {
+                let mut builder = NodeBuilder::new(Role::CheckBox);
+                builder.push_child(CHECKBOX_LABEL_ID);
+                builder.build()
+            })
+            .node(CHECKBOX_LABEL_ID, {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_name(CHECKBOX_LABEL);
+                builder.build()
+            })
+            .node(RADIO_BUTTON_ID, {
+                let mut builder = NodeBuilder::new(Role::RadioButton);
+                builder.push_child(RADIO_BUTTON_LABEL_ID);
+                builder.build()
+            })
+            .node(RADIO_BUTTON_LABEL_ID, {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_name(RADIO_BUTTON_LABEL);
+                builder.build()
+            })
+            .node(MENU_BUTTON_ID, {
+                let mut builder = NodeBuilder::new(Role::Button);
+                builder.push_child(MENU_BUTTON_LABEL_ID);
+                builder.build()
+            })
+            .node(MENU_BUTTON_LABEL_ID, {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_name(MENU_BUTTON_LABEL);
+                builder.build()
+            })
+            .node(MENU_ID, {
+                let mut builder = NodeBuilder::new(Role::Menu);
+                builder.set_children([MENU_ITEM_ID, MENU_ITEM_CHECKBOX_ID, MENU_ITEM_RADIO_ID]);
+                builder.build()
+            })
+            .node(MENU_ITEM_ID, {
+                let mut builder = NodeBuilder::new(Role::MenuItem);
+                builder.push_child(MENU_ITEM_LABEL_ID);
+                builder.build()
+            })
+            .node(MENU_ITEM_LABEL_ID, {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_name(MENU_ITEM_LABEL);
+                builder.build()
+            })
+            .node(MENU_ITEM_CHECKBOX_ID, {
+                let mut builder = NodeBuilder::new(Role::MenuItemCheckBox);
+                builder.push_child(MENU_ITEM_CHECKBOX_LABEL_ID);
+                builder.build()
+            })
+            .node(MENU_ITEM_CHECKBOX_LABEL_ID, {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_name(MENU_ITEM_CHECKBOX_LABEL);
+                builder.build()
+            })
+            .node(MENU_ITEM_RADIO_ID, {
+                let mut builder = NodeBuilder::new(Role::MenuItemRadio);
+                builder.push_child(MENU_ITEM_RADIO_LABEL_ID);
+                builder.build()
+            })
+            .node(MENU_ITEM_RADIO_LABEL_ID, {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_name(MENU_ITEM_RADIO_LABEL);
+                builder.build()
+            })
+            .build();
         let tree = crate::Tree::new(update, false);
         assert_eq!(
             Some(DEFAULT_BUTTON_LABEL.into()),
@@ -1177,4 +2354,842 @@ mod tests {
             tree.state().node_by_id(MENU_ITEM_RADIO_ID).unwrap().name()
         );
     }
+
+    fn range_tree(configure: impl FnOnce(&mut NodeBuilder)) -> crate::Tree {
+        const ROOT_ID: NodeId = NodeId(0);
+        const SLIDER_ID: NodeId = NodeId(1);
+
+        let root = {
+            let mut builder = NodeBuilder::new(Role::Window);
+            builder.set_children(vec![SLIDER_ID]);
+            builder.build()
+        };
+        let slider = {
+            let mut builder = NodeBuilder::new(Role::Slider);
+            configure(&mut builder);
+            builder.build()
+        };
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, root)
+            .node(SLIDER_ID, slider)
+            .build();
+        crate::Tree::new(update, false)
+    }
+
+    #[test]
+    fn numeric_value_steps_are_clamped() {
+        let tree = range_tree(|builder| {
+            builder.set_numeric_value(9.0);
+            builder.set_min_numeric_value(0.0);
+            builder.set_max_numeric_value(10.0);
+            builder.set_numeric_value_step(2.0);
+        });
+        let slider = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert_eq!(Some(10.0), slider.next_numeric_value());
+        assert_eq!(Some(7.0), slider.previous_numeric_value());
+
+        let tree = range_tree(|builder| {
+            builder.set_numeric_value(1.0);
+            builder.set_min_numeric_value(0.0);
+            builder.set_max_numeric_value(10.0);
+            builder.set_numeric_value_step(2.0);
+        });
+        let slider = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert_eq!(Some(3.0), slider.next_numeric_value());
+        assert_eq!(Some(0.0), slider.previous_numeric_value());
+    }
+
+    #[test]
+    fn numeric_value_step_defaults_to_tenth_of_range() {
+        let tree = range_tree(|builder| {
+            builder.set_numeric_value(50.0);
+            builder.set_min_numeric_value(0.0);
+            builder.set_max_numeric_value(100.0);
+        });
+        let slider = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert_eq!(Some(60.0), slider.next_numeric_value());
+        assert_eq!(Some(40.0), slider.previous_numeric_value());
+
+        let tree = range_tree(|builder| {
+            builder.set_numeric_value(50.0);
+        });
+        let slider = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert!(slider.next_numeric_value().is_none());
+        assert!(slider.previous_numeric_value().is_none());
+    }
+
+    #[test]
+    fn value_text_takes_precedence_over_numeric_value() {
+        let tree = range_tree(|builder| {
+            builder.set_numeric_value(2.0);
+            builder.set_value("Medium");
+        });
+        let slider = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert_eq!(Some("Medium".into()), slider.value_text_or_formatted());
+
+        let tree = range_tree(|builder| {
+            builder.set_numeric_value(2.0);
+        });
+        let slider = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert_eq!(Some("2".into()), slider.value_text_or_formatted());
+
+        let tree = range_tree(|_| {});
+        let slider = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert!(slider.value_text_or_formatted().is_none());
+    }
+
+    #[test]
+    fn nested_busy_regions() {
+        const ROOT_ID: NodeId = NodeId(0);
+        const OUTER_ID: NodeId = NodeId(1);
+        const INNER_ID: NodeId = NodeId(2);
+        const ITEM_ID: NodeId = NodeId(3);
+        const SIBLING_ID: NodeId = NodeId(4);
+
+        let node = |role, children: Vec<NodeId>, busy: bool| {
+            let mut builder = NodeBuilder::new(role);
+            builder.set_children(children);
+            if busy {
+                builder.set_busy();
+            }
+            builder.build()
+        };
+        let build_tree = |outer_busy, inner_busy| {
+            let update = TreeUpdateBuilder::new()
+                .tree_root(ROOT_ID)
+                .node(
+                    ROOT_ID,
+                    node(Role::Window, vec![OUTER_ID, SIBLING_ID], false),
+                )
+                .node(OUTER_ID, node(Role::Feed, vec![INNER_ID], outer_busy))
+                .node(INNER_ID, node(Role::List, vec![ITEM_ID], inner_busy))
+                .node(ITEM_ID, node(Role::ListItem, vec![], false))
+                .node(SIBLING_ID, node(Role::Button, vec![], false))
+                .build();
+            crate::Tree::new(update, false)
+        };
+
+        let tree = build_tree(true, false);
+        let state = tree.state();
+        assert!(!state.node_by_id(ITEM_ID).unwrap().is_busy());
+        assert!(state.node_by_id(ITEM_ID).unwrap().is_within_busy_subtree());
+        assert!(state.node_by_id(INNER_ID).unwrap().is_within_busy_subtree());
+        assert!(!state
+            .node_by_id(SIBLING_ID)
+            .unwrap()
+            .is_within_busy_subtree());
+        assert!(!state.root().is_within_busy_subtree());
+
+        let tree = build_tree(false, true);
+        let state = tree.state();
+        assert!(state.node_by_id(ITEM_ID).unwrap().is_within_busy_subtree());
+        assert!(state.node_by_id(INNER_ID).unwrap().is_within_busy_subtree());
+        assert!(!state.node_by_id(OUTER_ID).unwrap().is_within_busy_subtree());
+    }
+
+    #[test]
+    fn changed_fields() {
+        use crate::{fields_changed, ChangedNodeFields};
+        use accesskit::{Affine, Toggled};
+
+        const ROOT_ID: NodeId = NodeId(0);
+        const NODE_ID: NodeId = NodeId(1);
+        const OTHER_ID: NodeId = NodeId(2);
+        const CHILD_ID: NodeId = NodeId(3);
+        type Configure<'a> = dyn Fn(&mut NodeBuilder) + 'a;
+
+        let build_tree = |configure: &Configure<'_>, node_first: bool| {
+            let root = {
+                let mut builder = NodeBuilder::new(Role::Window);
+                if node_first {
+                    builder.set_children(vec![NODE_ID, OTHER_ID]);
+                } else {
+                    builder.set_children(vec![OTHER_ID, NODE_ID]);
+                }
+                builder.build()
+            };
+            let node = {
+                let mut builder = NodeBuilder::new(Role::CheckBox);
+                builder.set_name("Name");
+                builder.set_children(vec![CHILD_ID]);
+                configure(&mut builder);
+                builder.build()
+            };
+            let has_child = node.children().contains(&CHILD_ID);
+            let mut update_builder = TreeUpdateBuilder::new()
+                .tree_root(ROOT_ID)
+                .node(ROOT_ID, root)
+                .node(NODE_ID, node)
+                .node(OTHER_ID, NodeBuilder::new(Role::Button).build());
+            if has_child {
+                update_builder =
+                    update_builder.node(CHILD_ID, NodeBuilder::new(Role::Label).build());
+            }
+            crate::Tree::new(update_builder.build(), false)
+        };
+        let unchanged = |_: &mut NodeBuilder| {};
+        let old_tree = build_tree(&unchanged, true);
+        let old_node = old_tree.state().node_by_id(NODE_ID).unwrap();
+
+        let cases: &[(&Configure<'_>, bool, ChangedNodeFields)] = &[
+            (&unchanged, true, ChangedNodeFields::default()),
+            (&unchanged, false, ChangedNodeFields::PARENT),
+            (
+                &|builder| builder.set_role(Role::Switch),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::ROLE,
+            ),
+            (
+                &|builder| builder.set_children(vec![]),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::CHILDREN,
+            ),
+            (
+                &|builder| builder.set_name("Other name"),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::NAME,
+            ),
+            (
+                &|builder| builder.set_description("Description"),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::DESCRIPTION,
+            ),
+            (
+                &|builder| builder.set_value("Value"),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::VALUE,
+            ),
+            (
+                &|builder| builder.set_numeric_value(1.0),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::VALUE,
+            ),
+            (
+                &|builder| builder.set_toggled(Toggled::True),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::TOGGLED,
+            ),
+            (
+                &|builder| builder.set_bounds(Rect::new(0.0, 0.0, 10.0, 10.0)),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::BOUNDS,
+            ),
+            (
+                &|builder| builder.set_transform(Affine::scale(2.0)),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::BOUNDS,
+            ),
+            (
+                &|builder| builder.set_placeholder("Placeholder"),
+                true,
+                ChangedNodeFields::DATA,
+            ),
+            (
+                &|builder| builder.set_active_descendant(NodeId(1)),
+                true,
+                ChangedNodeFields::DATA | ChangedNodeFields::ACTIVE_DESCENDANT,
+            ),
+            (
+                &|builder| {
+                    builder.set_name("Other name");
+                    builder.set_toggled(Toggled::False);
+                },
+                false,
+                ChangedNodeFields::PARENT
+                    | ChangedNodeFields::DATA
+                    | ChangedNodeFields::NAME
+                    | ChangedNodeFields::TOGGLED,
+            ),
+        ];
+        for (configure, node_first, expected) in cases {
+            let new_tree = build_tree(configure, *node_first);
+            let new_node = new_tree.state().node_by_id(NODE_ID).unwrap();
+            let changed = fields_changed(&old_node, &new_node);
+            assert_eq!(*expected, changed);
+            assert_eq!(expected.is_empty(), changed.is_empty());
+            assert!(changed.contains(*expected));
+        }
+    }
+
+    #[test]
+    fn next_and_previous_with_role() {
+        let tree = test_tree();
+        let state = tree.state();
+        let root = state.root();
+        assert_eq!(
+            Some(LABEL_1_1_ID),
+            root.next_with_role(Role::Label, &test_tree_filter)
+                .map(|node| node.id())
+        );
+        let label_1_1 = state.node_by_id(LABEL_1_1_ID).unwrap();
+        assert_eq!(
+            Some(LABEL_2_0_ID),
+            label_1_1
+                .next_with_role(Role::Label, &test_tree_filter)
+                .map(|node| node.id())
+        );
+        let label_2_0 = state.node_by_id(LABEL_2_0_ID).unwrap();
+        assert_eq!(
+            Some(LABEL_3_1_0_ID),
+            label_2_0
+                .next_with_role(Role::Label, &test_tree_filter)
+                .map(|node| node.id())
+        );
+        assert_eq!(
+            Some(LABEL_1_1_ID),
+            label_2_0
+                .previous_with_role(Role::Label, &test_tree_filter)
+                .map(|node| node.id())
+        );
+        assert!(label_1_1
+            .previous_with_role(Role::Label, &test_tree_filter)
+            .is_none());
+        // Hidden buttons are skipped, along with their subtrees.
+        assert_eq!(
+            Some(BUTTON_3_2_ID),
+            root.next_with_role(Role::Button, &test_tree_filter)
+                .map(|node| node.id())
+        );
+        assert!(root
+            .next_with_role(Role::GenericContainer, &test_tree_filter)
+            .is_none());
+        let button_3_2 = state.node_by_id(BUTTON_3_2_ID).unwrap();
+        assert!(button_3_2
+            .next_with_role(Role::Label, &test_tree_filter)
+            .is_none());
+    }
+
+    #[test]
+    fn action_verb() {
+        use accesskit::{Action, DefaultActionVerb, HasPopup, Toggled};
+
+        use crate::ActionVerb;
+
+        fn clickable(role: Role) -> NodeBuilder {
+            let mut builder = NodeBuilder::new(role);
+            builder.add_action(Action::Default);
+            builder
+        }
+        fn toggled(role: Role, toggled: Toggled) -> NodeBuilder {
+            let mut builder = clickable(role);
+            builder.set_toggled(toggled);
+            builder
+        }
+        fn expanded(mut builder: NodeBuilder, expanded: bool) -> NodeBuilder {
+            builder.set_expanded(expanded);
+            builder
+        }
+        fn explicit(role: Role, verb: DefaultActionVerb) -> NodeBuilder {
+            let mut builder = NodeBuilder::new(role);
+            builder.set_default_action_verb(verb);
+            builder
+        }
+        fn with_actions(role: Role, actions: &[Action]) -> NodeBuilder {
+            let mut builder = NodeBuilder::new(role);
+            for action in actions {
+                builder.add_action(*action);
+            }
+            builder
+        }
+        fn adjustable(role: Role, actions: &[Action]) -> NodeBuilder {
+            let mut builder = with_actions(role, actions);
+            builder.set_numeric_value(5.0);
+            builder
+        }
+
+        let cases = [
+            (clickable(Role::Button), Some(ActionVerb::Click)),
+            (NodeBuilder::new(Role::Button), None),
+            (NodeBuilder::new(Role::Label), None),
+            (
+                explicit(Role::Button, DefaultActionVerb::Press),
+                Some(ActionVerb::Click),
+            ),
+            (
+                explicit(Role::GenericContainer, DefaultActionVerb::ClickAncestor),
+                None,
+            ),
+            (
+                explicit(Role::Button, DefaultActionVerb::Open),
+                Some(ActionVerb::Open),
+            ),
+            (
+                toggled(Role::CheckBox, Toggled::False),
+                Some(ActionVerb::Check),
+            ),
+            (
+                toggled(Role::CheckBox, Toggled::Mixed),
+                Some(ActionVerb::Check),
+            ),
+            (
+                toggled(Role::CheckBox, Toggled::True),
+                Some(ActionVerb::Uncheck),
+            ),
+            (
+                toggled(Role::MenuItemCheckBox, Toggled::True),
+                Some(ActionVerb::Uncheck),
+            ),
+            (
+                toggled(Role::RadioButton, Toggled::False),
+                Some(ActionVerb::Check),
+            ),
+            (
+                toggled(Role::Switch, Toggled::True),
+                Some(ActionVerb::Toggle),
+            ),
+            (
+                toggled(Role::Button, Toggled::False),
+                Some(ActionVerb::Toggle),
+            ),
+            (
+                explicit(Role::CheckBox, DefaultActionVerb::Uncheck),
+                Some(ActionVerb::Uncheck),
+            ),
+            (
+                expanded(clickable(Role::TreeItem), false),
+                Some(ActionVerb::Expand),
+            ),
+            (
+                expanded(clickable(Role::ComboBox), true),
+                Some(ActionVerb::Collapse),
+            ),
+            (
+                expanded(with_actions(Role::TreeItem, &[Action::Expand]), false),
+                Some(ActionVerb::Expand),
+            ),
+            (
+                expanded(with_actions(Role::TreeItem, &[Action::Expand]), true),
+                None,
+            ),
+            (clickable(Role::Link), Some(ActionVerb::Jump)),
+            (NodeBuilder::new(Role::Link), None),
+            (
+                explicit(Role::Cell, DefaultActionVerb::Jump),
+                Some(ActionVerb::Jump),
+            ),
+            (
+                adjustable(Role::Slider, &[Action::Increment, Action::Decrement]),
+                Some(ActionVerb::Adjust),
+            ),
+            (
+                adjustable(Role::SpinButton, &[Action::Default, Action::SetValue]),
+                Some(ActionVerb::Adjust),
+            ),
+            (adjustable(Role::ProgressIndicator, &[]), None),
+            (
+                {
+                    let mut builder = clickable(Role::Button);
+                    builder.set_has_popup(HasPopup::Menu);
+                    builder
+                },
+                Some(ActionVerb::Open),
+            ),
+        ];
+
+        let mut update = TreeUpdateBuilder::new().tree_root(ROOT_ID);
+        let mut children = Vec::new();
+        for (i, (builder, _)) in cases.iter().enumerate() {
+            let id = NodeId(i as u64 + 1);
+            children.push(id);
+            update = update.node(id, builder.clone().build());
+        }
+        let update = update
+            .node(ROOT_ID, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(children);
+                builder.build()
+            })
+            .build();
+        let tree = crate::Tree::new(update, false);
+        for (i, (builder, expected)) in cases.iter().enumerate() {
+            let node = tree.state().node_by_id(NodeId(i as u64 + 1)).unwrap();
+            assert_eq!(*expected, node.action_verb(), "case {i}: {builder:?}");
+        }
+    }
+
+    #[test]
+    fn current_item() {
+        use accesskit::AriaCurrent;
+
+        use crate::{fields_changed, ChangedNodeFields};
+
+        const NAV_ID: NodeId = NodeId(1);
+        const LINK_1_ID: NodeId = NodeId(2);
+        const LINK_2_ID: NodeId = NodeId(3);
+        const TAB_LIST_ID: NodeId = NodeId(4);
+        const TAB_1_ID: NodeId = NodeId(5);
+        const TAB_2_ID: NodeId = NodeId(6);
+
+        let build = |current_link: Option<NodeId>, selected_tabs: &[NodeId], multi: bool| {
+            let link = |id| {
+                let mut builder = NodeBuilder::new(Role::Link);
+                if current_link == Some(id) {
+                    builder.set_aria_current(AriaCurrent::Page);
+                } else if current_link.is_some() {
+                    builder.set_aria_current(AriaCurrent::False);
+                }
+                builder.build()
+            };
+            let tab = |id| {
+                let mut builder = NodeBuilder::new(Role::Tab);
+                builder.set_selected(selected_tabs.contains(&id));
+                builder.build()
+            };
+            let update = TreeUpdateBuilder::new()
+                .tree_root(ROOT_ID)
+                .node(ROOT_ID, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NAV_ID, TAB_LIST_ID]);
+                    builder.build()
+                })
+                .node(NAV_ID, {
+                    let mut builder = NodeBuilder::new(Role::Navigation);
+                    builder.set_children(vec![LINK_1_ID, LINK_2_ID]);
+                    builder.build()
+                })
+                .node(LINK_1_ID, link(LINK_1_ID))
+                .node(LINK_2_ID, link(LINK_2_ID))
+                .node(TAB_LIST_ID, {
+                    let mut builder = NodeBuilder::new(Role::TabList);
+                    builder.set_children(vec![TAB_1_ID, TAB_2_ID]);
+                    if multi {
+                        builder.set_multiselectable();
+                    }
+                    builder.build()
+                })
+                .node(TAB_1_ID, tab(TAB_1_ID))
+                .node(TAB_2_ID, tab(TAB_2_ID))
+                .build();
+            crate::Tree::new(update, false)
+        };
+        let current = |tree: &crate::Tree, container| {
+            tree.state()
+                .node_by_id(container)
+                .unwrap()
+                .current_descendant(&crate::common_filter)
+                .map(|node| node.id())
+        };
+
+        let tree = build(Some(LINK_2_ID), &[TAB_1_ID], false);
+        assert!(!tree.state().node_by_id(LINK_1_ID).unwrap().is_current());
+        assert!(tree.state().node_by_id(LINK_2_ID).unwrap().is_current());
+        assert_eq!(Some(LINK_2_ID), current(&tree, NAV_ID));
+        assert_eq!(Some(TAB_1_ID), current(&tree, TAB_LIST_ID));
+        // Both the link and the tab are current, so neither is unique
+        // from the root's point of view.
+        assert_eq!(None, current(&tree, ROOT_ID));
+
+        let tree = build(None, &[], false);
+        assert_eq!(None, current(&tree, NAV_ID));
+        assert_eq!(None, current(&tree, TAB_LIST_ID));
+        assert_eq!(None, current(&tree, ROOT_ID));
+
+        // The fallback only applies when exactly one tab is selected
+        // in a single-selection tab list.
+        let tree = build(None, &[TAB_1_ID, TAB_2_ID], false);
+        assert_eq!(None, current(&tree, TAB_LIST_ID));
+        let tree = build(None, &[TAB_2_ID], true);
+        assert_eq!(None, current(&tree, TAB_LIST_ID));
+
+        let old_tree = build(Some(LINK_1_ID), &[TAB_1_ID], false);
+        let new_tree = build(Some(LINK_2_ID), &[TAB_2_ID], false);
+        for id in [LINK_1_ID, LINK_2_ID, TAB_1_ID, TAB_2_ID] {
+            let old_node = old_tree.state().node_by_id(id).unwrap();
+            let new_node = new_tree.state().node_by_id(id).unwrap();
+            let changed = fields_changed(&old_node, &new_node);
+            assert!(changed.contains(ChangedNodeFields::CURRENT), "{id:?}");
+        }
+        let unchanged = build(Some(LINK_2_ID), &[TAB_2_ID], false);
+        for id in [LINK_1_ID, LINK_2_ID, TAB_1_ID, TAB_2_ID] {
+            let old_node = unchanged.state().node_by_id(id).unwrap();
+            let new_node = new_tree.state().node_by_id(id).unwrap();
+            assert!(fields_changed(&old_node, &new_node).is_empty());
+        }
+    }
+
+    #[test]
+    fn selection_container() {
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NodeId(1)]);
+                builder.build()
+            })
+            .node(NodeId(1), {
+                let mut builder = NodeBuilder::new(Role::ListBox);
+                builder.set_multiselectable();
+                builder.set_children(vec![NodeId(2)]);
+                builder.build()
+            })
+            .node(NodeId(2), {
+                let mut builder = NodeBuilder::new(Role::GenericContainer);
+                builder.set_children(vec![NodeId(3)]);
+                builder.build()
+            })
+            .node(NodeId(3), {
+                let mut builder = NodeBuilder::new(Role::ListBoxOption);
+                builder.set_selected(true);
+                builder.build()
+            })
+            .build();
+        let tree = crate::Tree::new(update, false);
+        let state = tree.state();
+        let list = state.node_by_id(NodeId(1)).unwrap();
+        assert!(list.is_container_with_selectable_children());
+        assert!(list.is_multiselectable());
+        assert!(!list.is_required());
+        let option = state.node_by_id(NodeId(3)).unwrap();
+        assert_eq!(
+            Some(NodeId(1)),
+            option
+                .selection_container(&test_tree_filter)
+                .map(|node| node.id())
+        );
+        assert!(list.selection_container(&test_tree_filter).is_none());
+    }
+
+    #[test]
+    fn selection_container_descendants() {
+        let node = |role, children: Vec<NodeId>, selected: Option<bool>| {
+            let mut builder = NodeBuilder::new(role);
+            builder.set_children(children);
+            if let Some(selected) = selected {
+                builder.set_selected(selected);
+            }
+            builder.build()
+        };
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, node(Role::Window, vec![NodeId(1)], None))
+            .node(
+                NodeId(1),
+                node(Role::Tree, vec![NodeId(2), NodeId(5)], None),
+            )
+            .node(
+                NodeId(2),
+                node(Role::TreeItem, vec![NodeId(3)], Some(false)),
+            )
+            .node(NodeId(3), node(Role::Group, vec![NodeId(4)], None))
+            .node(NodeId(4), node(Role::TreeItem, vec![], Some(true)))
+            .node(NodeId(5), node(Role::ComboBox, vec![NodeId(6)], None))
+            .node(NodeId(6), node(Role::MenuListPopup, vec![NodeId(7)], None))
+            .node(NodeId(7), node(Role::MenuListOption, vec![], Some(true)))
+            .build();
+        let tree = crate::Tree::new(update, false);
+        let state = tree.state();
+        let descendant_ids = |id| {
+            state
+                .node_by_id(id)
+                .unwrap()
+                .selection_container_descendants(crate::common_filter)
+                .into_iter()
+                .map(|node| node.id())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![NodeId(2), NodeId(3), NodeId(4), NodeId(5)],
+            descendant_ids(NodeId(1))
+        );
+        assert_eq!(vec![NodeId(6)], descendant_ids(NodeId(5)));
+        assert_eq!(vec![NodeId(7)], descendant_ids(NodeId(6)));
+        for (id, container_id) in [(4, 1), (5, 1), (7, 6)] {
+            let node = state.node_by_id(NodeId(id)).unwrap();
+            assert_eq!(
+                Some(NodeId(container_id)),
+                node.selection_container(&crate::common_filter)
+                    .map(|node| node.id())
+            );
+        }
+    }
+
+    #[test]
+    fn scrollable() {
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, {
+                let mut builder = NodeBuilder::new(Role::ScrollView);
+                builder.set_scroll_y(20.0);
+                builder.set_scroll_y_max(100.0);
+                builder.set_scroll_x_min(10.0);
+                builder.set_scroll_x_max(10.0);
+                builder.build()
+            })
+            .build();
+        let tree = crate::Tree::new(update, false);
+        let root = tree.state().root();
+        assert!(root.is_vertically_scrollable());
+        assert!(!root.is_horizontally_scrollable());
+        assert!(root.is_scrollable());
+        assert_eq!(Some(20.0), root.scroll_y());
+        assert_eq!(None, root.scroll_x());
+
+        let tree = test_tree();
+        assert!(!tree.state().root().is_scrollable());
+    }
+
+    #[test]
+    fn state_accessors() {
+        const ROOT_ID: NodeId = NodeId(0);
+        const FORM_ID: NodeId = NodeId(1);
+        const INPUT_ID: NodeId = NodeId(2);
+        const BUTTON_ID: NodeId = NodeId(3);
+        const HIDDEN_ID: NodeId = NodeId(4);
+
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![FORM_ID, HIDDEN_ID]);
+                builder.build()
+            })
+            .node(FORM_ID, {
+                let mut builder = NodeBuilder::new(Role::Form);
+                builder.set_children(vec![INPUT_ID, BUTTON_ID]);
+                builder.build()
+            })
+            .node(INPUT_ID, {
+                let mut builder = NodeBuilder::new(Role::TextInput);
+                builder.set_read_only();
+                builder.build()
+            })
+            .node(BUTTON_ID, {
+                let mut builder = NodeBuilder::new(Role::Button);
+                builder.set_disabled();
+                builder.build()
+            })
+            .node(HIDDEN_ID, {
+                let mut builder = NodeBuilder::new(Role::Label);
+                builder.set_hidden();
+                builder.build()
+            })
+            .focus(INPUT_ID)
+            .build();
+        let mut tree = crate::Tree::new(update, true);
+        let state = tree.state();
+        let node = |id| state.node_by_id(id).unwrap();
+
+        assert!(node(HIDDEN_ID).is_hidden());
+        assert!(!node(INPUT_ID).is_hidden());
+        assert!(node(BUTTON_ID).is_disabled());
+        assert!(!node(INPUT_ID).is_disabled());
+        assert!(node(INPUT_ID).is_read_only());
+        // Roles that don't support the read-only state are always read-only.
+        assert!(node(BUTTON_ID).is_read_only());
+        assert!(node(BUTTON_ID).is_read_only_or_disabled());
+        assert!(node(ROOT_ID).is_focused_ancestor());
+        assert!(node(FORM_ID).is_focused_ancestor());
+        assert!(!node(INPUT_ID).is_focused_ancestor());
+        assert!(!node(BUTTON_ID).is_focused_ancestor());
+        assert!(!node(HIDDEN_ID).is_focused_ancestor());
+
+        tree.update_host_focus_state(false);
+        let state = tree.state();
+        assert!(!state.node_by_id(ROOT_ID).unwrap().is_focused_ancestor());
+    }
+
+    #[test]
+    fn is_protected() {
+        const ROOT_ID: NodeId = NodeId(0);
+        const PASSWORD_ID: NodeId = NodeId(1);
+        const PIN_ID: NodeId = NodeId(2);
+        const NAME_ID: NodeId = NodeId(3);
+
+        let update = TreeUpdateBuilder::new()
+            .tree_root(ROOT_ID)
+            .node(ROOT_ID, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![PASSWORD_ID, PIN_ID, NAME_ID]);
+                builder.build()
+            })
+            .node(PASSWORD_ID, NodeBuilder::new(Role::PasswordInput).build())
+            .node(PIN_ID, {
+                let mut builder = NodeBuilder::new(Role::NumberInput);
+                builder.set_protected();
+                builder.build()
+            })
+            .node(NAME_ID, NodeBuilder::new(Role::TextInput).build())
+            .build();
+        let tree = crate::Tree::new(update, false);
+        let state = tree.state();
+        assert!(state.node_by_id(PASSWORD_ID).unwrap().is_protected());
+        assert!(state.node_by_id(PIN_ID).unwrap().is_protected());
+        assert!(!state.node_by_id(NAME_ID).unwrap().is_protected());
+    }
+
+    #[test]
+    fn custom_actions() {
+        use crate::{fields_changed, ChangedNodeFields};
+        use accesskit::CustomAction;
+
+        const ROOT_ID: NodeId = NodeId(0);
+        const ITEM_ID: NodeId = NodeId(1);
+
+        let build_update = |actions: &[(i32, &str)]| {
+            let root = {
+                let mut builder = NodeBuilder::new(Role::List);
+                builder.set_children(vec![ITEM_ID]);
+                builder.build()
+            };
+            let item = {
+                let mut builder = NodeBuilder::new(Role::ListItem);
+                builder.set_custom_actions(
+                    actions
+                        .iter()
+                        .map(|(id, description)| CustomAction {
+                            id: *id,
+                            description: (*description).into(),
+                        })
+                        .collect::<Vec<_>>(),
+                );
+                builder.build()
+            };
+            TreeUpdateBuilder::new()
+                .tree_root(ROOT_ID)
+                .node(ROOT_ID, root)
+                .node(ITEM_ID, item)
+                .build()
+        };
+
+        let tree = crate::Tree::new(
+            build_update(&[(3, "Move down"), (1, "Move up"), (2, "Delete")]),
+            false,
+        );
+        let item = tree.state().node_by_id(ITEM_ID).unwrap();
+        assert_eq!(
+            vec![(3, "Move down"), (1, "Move up"), (2, "Delete")],
+            item.custom_actions().collect::<Vec<_>>()
+        );
+        assert_eq!(Some("Move up"), item.custom_action_description(1));
+        assert_eq!(None, item.custom_action_description(4));
+        assert_eq!(0, tree.state().root().custom_actions().len());
+
+        let reordered = crate::Tree::new(
+            build_update(&[(1, "Move up"), (3, "Move down"), (2, "Delete")]),
+            false,
+        );
+        let renamed = crate::Tree::new(
+            build_update(&[(3, "Move down"), (1, "Move up"), (2, "Remove")]),
+            false,
+        );
+        let same = crate::Tree::new(
+            build_update(&[(3, "Move down"), (1, "Move up"), (2, "Delete")]),
+            false,
+        );
+        for (other, expected) in [
+            (
+                &reordered,
+                ChangedNodeFields::DATA | ChangedNodeFields::CUSTOM_ACTIONS,
+            ),
+            (
+                &renamed,
+                ChangedNodeFields::DATA | ChangedNodeFields::CUSTOM_ACTIONS,
+            ),
+            (&same, ChangedNodeFields::default()),
+        ] {
+            let other_item = other.state().node_by_id(ITEM_ID).unwrap();
+            assert_eq!(expected, fields_changed(&item, &other_item));
+        }
+    }
 }
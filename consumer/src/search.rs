@@ -0,0 +1,422 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::NodeId;
+use std::ops::Range;
+
+use crate::{filters::FilterResult, node::Node, tree::State as TreeState};
+
+/// Options for [`TreeState::search_text`].
+#[derive(Clone, Debug, Default)]
+pub struct TextSearchOptions {
+    /// If false, matching ignores case.
+    pub case_sensitive: bool,
+    /// If true, a match must not be preceded or followed by
+    /// an alphanumeric character.
+    pub whole_word: bool,
+    /// If set, only nodes that follow this one in document order,
+    /// including its descendants, are searched. This is how an AT
+    /// implements "find next"; the search doesn't wrap around.
+    pub start_after: Option<NodeId>,
+    /// If true, common accented Latin letters match their base letters.
+    #[cfg(feature = "diacritic_folding")]
+    pub ignore_diacritics: bool,
+}
+
+/// A match found by [`TreeState::search_text`].
+#[derive(Clone)]
+pub struct TextSearchMatch<'a> {
+    pub node: Node<'a>,
+    /// The text of the node that was searched, which is its computed name,
+    /// or its value if it has no name.
+    pub text: String,
+    /// The byte range of the match within `text`.
+    pub range: Range<usize>,
+}
+
+#[cfg(feature = "diacritic_folding")]
+fn strip_diacritic(c: char) -> char {
+    match c {
+        'À'..='Å' => 'A',
+        'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+        'Ā' | 'Ă' | 'Ą' => 'A',
+        'Ç' | 'Ć' | 'Č' => 'C',
+        'ç' | 'ć' | 'č' => 'c',
+        'Ď' => 'D',
+        'ď' => 'd',
+        'È'..='Ë' | 'Ē' | 'Ė' | 'Ę' | 'Ě' => 'E',
+        'è'..='ë' | 'ē' | 'ė' | 'ę' | 'ě' => 'e',
+        'Ì'..='Ï' | 'Ī' | 'Į' => 'I',
+        'ì'..='ï' | 'ī' | 'į' => 'i',
+        'Ñ' | 'Ń' | 'Ň' => 'N',
+        'ñ' | 'ń' | 'ň' => 'n',
+        'Ò'..='Ö' | 'Ø' | 'Ō' | 'Ő' => 'O',
+        'ò'..='ö' | 'ø' | 'ō' | 'ő' => 'o',
+        'Ř' => 'R',
+        'ř' => 'r',
+        'Ś' | 'Š' => 'S',
+        'ś' | 'š' => 's',
+        'Ť' => 'T',
+        'ť' => 't',
+        'Ù'..='Ü' | 'Ū' | 'Ů' | 'Ű' => 'U',
+        'ù'..='ü' | 'ū' | 'ů' | 'ű' => 'u',
+        'Ý' | 'Ÿ' => 'Y',
+        'ý' | 'ÿ' => 'y',
+        'Ź' | 'Ż' | 'Ž' => 'Z',
+        'ź' | 'ż' | 'ž' => 'z',
+        _ => c,
+    }
+}
+
+/// Text transformed for matching, along with the byte offset
+/// in the original text of each byte of the transformed text.
+struct FoldedText {
+    text: String,
+    original_offsets: Vec<usize>,
+}
+
+impl FoldedText {
+    fn new(original: &str, options: &TextSearchOptions) -> Self {
+        let mut text = String::with_capacity(original.len());
+        let mut original_offsets = Vec::with_capacity(original.len());
+        for (offset, c) in original.char_indices() {
+            #[cfg(feature = "diacritic_folding")]
+            let c = if options.ignore_diacritics {
+                strip_diacritic(c)
+            } else {
+                c
+            };
+            let start = text.len();
+            if options.case_sensitive {
+                text.push(c);
+            } else {
+                text.extend(c.to_lowercase());
+            }
+            original_offsets.resize(original_offsets.len() + text.len() - start, offset);
+        }
+        Self {
+            text,
+            original_offsets,
+        }
+    }
+
+    fn original_range(&self, original: &str, range: Range<usize>) -> Range<usize> {
+        let start = self.original_offsets[range.start];
+        let last = self.original_offsets[range.end - 1];
+        let end = last + original[last..].chars().next().unwrap().len_utf8();
+        start..end
+    }
+}
+
+fn is_word_boundary(text: &str, index: usize) -> bool {
+    let before = text[..index].chars().next_back();
+    let after = text[index..].chars().next();
+    !(before.is_some_and(char::is_alphanumeric) && after.is_some_and(char::is_alphanumeric))
+}
+
+struct SearchedNode<'a> {
+    node: Node<'a>,
+    text: String,
+    folded: FoldedText,
+    offset: usize,
+}
+
+struct TextSearch<'a, F: Fn(&Node) -> FilterResult> {
+    // Nodes remaining to be visited, in reverse document order.
+    stack: Vec<Node<'a>>,
+    filter: F,
+    query: String,
+    options: TextSearchOptions,
+    // The node currently being searched. Its text is computed only once,
+    // no matter how many matches it contains.
+    current: Option<SearchedNode<'a>>,
+}
+
+impl<'a, F: Fn(&Node) -> FilterResult> TextSearch<'a, F> {
+    fn new(state: &'a TreeState, query: &str, filter: F, options: &TextSearchOptions) -> Self {
+        let mut stack = Vec::new();
+        if query.is_empty() {
+            // There's nothing to find.
+        } else if let Some(start) = options.start_after {
+            if let Some(start) = state.node_by_id(start) {
+                // Collect the nodes that follow the start node in document
+                // order, skipping any that are inside an excluded subtree.
+                if filter(&start) != FilterResult::ExcludeSubtree {
+                    stack.extend(start.children());
+                }
+                let mut current = start;
+                loop {
+                    stack.extend(current.following_siblings());
+                    let Some(parent) = current.parent() else {
+                        break;
+                    };
+                    if filter(&parent) == FilterResult::ExcludeSubtree {
+                        stack.clear();
+                    }
+                    current = parent;
+                }
+                stack.reverse();
+            }
+        } else {
+            stack.push(state.root());
+        }
+        Self {
+            stack,
+            filter,
+            query: FoldedText::new(query, options).text,
+            options: options.clone(),
+            current: None,
+        }
+    }
+
+    fn next_node(&mut self) -> Option<SearchedNode<'a>> {
+        while let Some(node) = self.stack.pop() {
+            let result = (self.filter)(&node);
+            if result == FilterResult::ExcludeSubtree {
+                continue;
+            }
+            self.stack.extend(node.children().rev());
+            if result != FilterResult::Include {
+                continue;
+            }
+            if let Some(text) = node.name().or_else(|| node.value()) {
+                let folded = FoldedText::new(&text, &self.options);
+                return Some(SearchedNode {
+                    node,
+                    text,
+                    folded,
+                    offset: 0,
+                });
+            }
+        }
+        None
+    }
+}
+
+impl<'a, F: Fn(&Node) -> FilterResult> Iterator for TextSearch<'a, F> {
+    type Item = TextSearchMatch<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.current.is_none() {
+                self.current = Some(self.next_node()?);
+            }
+            let current = self.current.as_mut().unwrap();
+            let folded = &current.folded.text;
+            while let Some(index) = folded[current.offset..].find(&self.query) {
+                let start = current.offset + index;
+                let end = start + self.query.len();
+                current.offset = start + folded[start..].chars().next().unwrap().len_utf8();
+                if self.options.whole_word
+                    && !(is_word_boundary(folded, start) && is_word_boundary(folded, end))
+                {
+                    continue;
+                }
+                return Some(TextSearchMatch {
+                    node: current.node,
+                    range: current.folded.original_range(&current.text, start..end),
+                    text: current.text.clone(),
+                });
+            }
+            self.current = None;
+        }
+    }
+}
+
+impl TreeState {
+    /// Returns the nodes included by the filter whose computed name
+    /// (or value, if the node has no name) contains the query,
+    /// in document order. A node that contains several matches
+    /// is returned once for each of them.
+    pub fn search_text<'a>(
+        &'a self,
+        query: &str,
+        filter: impl Fn(&Node) -> FilterResult + 'a,
+        options: &TextSearchOptions,
+    ) -> impl Iterator<Item = TextSearchMatch<'a>> + 'a {
+        TextSearch::new(self, query, filter, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{NodeBuilder, NodeId, Role, TreeUpdateBuilder};
+
+    use super::TextSearchOptions;
+    use crate::{common_filter, tests::*};
+
+    fn search(
+        tree: &crate::Tree,
+        filter: fn(&crate::Node) -> crate::FilterResult,
+        query: &str,
+        options: &TextSearchOptions,
+    ) -> Vec<(NodeId, String)> {
+        tree.state()
+            .search_text(query, filter, options)
+            .map(|m| (m.node.id(), m.text[m.range].to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn filtered_document_order() {
+        let tree = test_tree();
+        assert_eq!(
+            vec![
+                (LABEL_1_1_ID, "label".into()),
+                (LABEL_2_0_ID, "label".into()),
+                (LABEL_3_1_0_ID, "label".into()),
+            ],
+            search(
+                &tree,
+                test_tree_filter,
+                "LABEL",
+                &TextSearchOptions::default()
+            )
+        );
+        assert!(search(
+            &tree,
+            test_tree_filter,
+            "LABEL",
+            &TextSearchOptions {
+                case_sensitive: true,
+                ..Default::default()
+            }
+        )
+        .is_empty());
+        assert!(search(
+            &tree,
+            test_tree_filter,
+            "hidden",
+            &TextSearchOptions::default()
+        )
+        .is_empty());
+        assert!(search(&tree, test_tree_filter, "", &TextSearchOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn find_next_does_not_wrap() {
+        let tree = test_tree();
+        let options = |start_after| TextSearchOptions {
+            start_after: Some(start_after),
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![(LABEL_3_1_0_ID, "label".into())],
+            search(&tree, test_tree_filter, "label", &options(LABEL_2_0_ID))
+        );
+        assert!(search(&tree, test_tree_filter, "label", &options(LABEL_3_1_0_ID)).is_empty());
+        // Descendants of the start node follow it in document order.
+        assert_eq!(
+            vec![(LABEL_2_0_ID, "label".into())],
+            search(&tree, test_tree_filter, "label", &options(PARAGRAPH_2_ID))[..1].to_vec()
+        );
+        // Starting inside a hidden subtree continues after that subtree.
+        assert_eq!(
+            vec![(LABEL_2_0_ID, "label".into())],
+            search(
+                &tree,
+                test_tree_filter,
+                "label",
+                &options(CONTAINER_1_2_0_HIDDEN_ID)
+            )[..1]
+                .to_vec()
+        );
+    }
+
+    fn names_tree(names: &[&str]) -> crate::Tree {
+        let mut builder = TreeUpdateBuilder::new().tree_root(ROOT_ID).node(ROOT_ID, {
+            let mut builder = NodeBuilder::new(Role::Window);
+            builder.set_children((1..=names.len() as u64).map(NodeId).collect::<Vec<_>>());
+            builder.build()
+        });
+        for (i, name) in names.iter().enumerate() {
+            let mut node = NodeBuilder::new(Role::Label);
+            node.set_name(*name);
+            builder = builder.node(NodeId(i as u64 + 1), node.build());
+        }
+        crate::Tree::new(builder.build(), false)
+    }
+
+    #[test]
+    fn whole_words_and_multiple_matches() {
+        let tree = names_tree(&["Cat catalog", "concatenate", "the CAT sat"]);
+        let options = TextSearchOptions {
+            whole_word: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![(NodeId(1), "Cat".into()), (NodeId(3), "CAT".into())],
+            search(&tree, common_filter, "cat", &options)
+        );
+        let matches = tree
+            .state()
+            .search_text("cat", common_filter, &TextSearchOptions::default())
+            .map(|m| (m.node.id(), m.range))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (NodeId(1), 0..3),
+                (NodeId(1), 4..7),
+                (NodeId(2), 3..6),
+                (NodeId(3), 4..7),
+            ],
+            matches
+        );
+    }
+
+    #[test]
+    fn ranges_in_folded_text() {
+        let tree = names_tree(&["İstanbul Straße"]);
+        let matches = tree
+            .state()
+            .search_text("STRASSE", common_filter, &TextSearchOptions::default())
+            .count();
+        // Lowercasing doesn't expand ß, so this doesn't match.
+        assert_eq!(0, matches);
+        assert_eq!(
+            vec![(NodeId(1), "Straße".into())],
+            search(
+                &tree,
+                common_filter,
+                "straße",
+                &TextSearchOptions::default()
+            )
+        );
+        assert_eq!(
+            vec![(NodeId(1), "İstanbul".into())],
+            search(
+                &tree,
+                common_filter,
+                "i̇stanbul",
+                &TextSearchOptions::default()
+            )
+        );
+    }
+
+    #[cfg(feature = "diacritic_folding")]
+    #[test]
+    fn ignore_diacritics() {
+        let tree = names_tree(&["Crème brûlée", "Creme"]);
+        let options = TextSearchOptions {
+            ignore_diacritics: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![(NodeId(1), "Crème".into()), (NodeId(2), "Creme".into())],
+            search(&tree, common_filter, "creme", &options)
+        );
+        assert_eq!(
+            vec![(NodeId(1), "brûlée".into())],
+            search(&tree, common_filter, "BRULEE", &options)
+        );
+        assert!(search(
+            &tree,
+            common_filter,
+            "brulee",
+            &TextSearchOptions::default()
+        )
+        .is_empty());
+    }
+}
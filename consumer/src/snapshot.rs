@@ -0,0 +1,332 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{Node as NodeData, NodeBuilder, NodeId, TreeUpdate};
+use std::collections::HashSet;
+
+use crate::tree::State as TreeState;
+
+/// The captured data of a subtree, as returned by
+/// [`TreeState::extract_subtree`], which can later be turned into
+/// an update that puts the subtree back the way it was.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubtreeSnapshot {
+    parent_and_index: Option<(NodeId, usize)>,
+    nodes: Vec<(NodeId, NodeData)>,
+}
+
+/// The reason that a [`SubtreeSnapshot`] can't be restored
+/// into the current tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestoreError {
+    /// The node that the subtree was a child of is no longer in the tree.
+    MissingParent(NodeId),
+    /// The subtree was the whole tree, but the tree now has
+    /// a different root.
+    RootReplaced(NodeId),
+    /// A node in the snapshot is now in the tree outside of the subtree
+    /// that would be replaced, so restoring it would move it.
+    IdInUse(NodeId),
+}
+
+impl std::fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingParent(id) => {
+                write!(f, "the subtree's parent #{} is no longer in the tree", id.0)
+            }
+            Self::RootReplaced(id) => {
+                write!(f, "the tree's root is now #{}", id.0)
+            }
+            Self::IdInUse(id) => {
+                write!(f, "node #{} is now used outside of the subtree", id.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RestoreError {}
+
+impl SubtreeSnapshot {
+    pub fn root_id(&self) -> NodeId {
+        self.nodes[0].0
+    }
+
+    /// Returns the captured nodes, in document order,
+    /// starting with the root of the subtree.
+    pub fn nodes(&self) -> &[(NodeId, NodeData)] {
+        &self.nodes
+    }
+
+    /// Returns an update that replaces whatever is now at the subtree's
+    /// position in `current` with the captured nodes. If the subtree's
+    /// root is no longer a child of its original parent, it's reinserted
+    /// at its original index, or at the end if the parent now has fewer
+    /// children. Nodes that were added to the subtree since it was
+    /// captured are removed. If the focused node is one of those,
+    /// focus moves to the subtree's root.
+    pub fn to_restoring_update(&self, current: &TreeState) -> Result<TreeUpdate, RestoreError> {
+        let root_id = self.root_id();
+        let mut nodes = Vec::new();
+        let replaced_root = match self.parent_and_index {
+            Some((parent_id, index)) => {
+                let Some(parent) = current.node_by_id(parent_id) else {
+                    return Err(RestoreError::MissingParent(parent_id));
+                };
+                if parent.data().children().contains(&root_id) {
+                    Some(root_id)
+                } else {
+                    let mut children = parent.data().children().to_vec();
+                    children.insert(index.min(children.len()), root_id);
+                    let mut builder = NodeBuilder::from_node(parent.data());
+                    builder.set_children(children);
+                    nodes.push((parent_id, builder.build()));
+                    None
+                }
+            }
+            None => {
+                if current.root_id() != root_id {
+                    return Err(RestoreError::RootReplaced(current.root_id()));
+                }
+                Some(root_id)
+            }
+        };
+
+        let mut replaced_ids = HashSet::new();
+        if let Some(replaced_root) = replaced_root {
+            let mut to_visit = vec![replaced_root];
+            while let Some(id) = to_visit.pop() {
+                replaced_ids.insert(id);
+                let node = current.node_by_id(id).unwrap();
+                to_visit.extend(node.data().children().iter());
+            }
+        }
+        for (id, _) in &self.nodes {
+            if current.has_node(*id) && !replaced_ids.contains(id) {
+                return Err(RestoreError::IdInUse(*id));
+            }
+        }
+
+        let focus = current.focus;
+        let focus = if replaced_ids.contains(&focus)
+            && !self.nodes.iter().any(|(node_id, _)| *node_id == focus)
+        {
+            root_id
+        } else {
+            focus
+        };
+        nodes.extend(self.nodes.iter().cloned());
+        Ok(TreeUpdate {
+            nodes,
+            tree: None,
+            focus,
+        })
+    }
+}
+
+impl TreeState {
+    /// Captures the data of the subtree rooted at the given node, along
+    /// with the subtree's position in its parent, so that it can later be
+    /// restored with [`SubtreeSnapshot::to_restoring_update`], e.g. to undo
+    /// a change or to revert a speculative one. Returns `None` if the node
+    /// isn't in the tree.
+    pub fn extract_subtree(&self, root_id: NodeId) -> Option<SubtreeSnapshot> {
+        let root = self.node_by_id(root_id)?;
+        let parent_and_index = root
+            .parent_and_index()
+            .map(|(parent, index)| (parent.id(), index));
+        let mut nodes = Vec::new();
+        let mut to_visit = vec![root_id];
+        while let Some(id) = to_visit.pop() {
+            let node = self.node_by_id(id).unwrap();
+            nodes.push((id, node.data().clone()));
+            to_visit.extend(node.data().children().iter().rev());
+        }
+        Some(SubtreeSnapshot {
+            parent_and_index,
+            nodes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{NodeBuilder, NodeId, Role, TreeUpdateBuilder};
+
+    use super::RestoreError;
+    use crate::tests::*;
+
+    fn apply(
+        tree: &mut crate::Tree,
+        nodes: impl FnOnce(&crate::Tree) -> Vec<(NodeId, accesskit::Node)>,
+    ) {
+        let nodes = nodes(tree);
+        tree.update(
+            TreeUpdateBuilder::new()
+                .nodes(nodes)
+                .focus(tree.state().focus)
+                .build(),
+        );
+    }
+
+    fn with_children(id: NodeId, tree: &crate::Tree, children: Vec<NodeId>) -> accesskit::Node {
+        let mut builder = NodeBuilder::from_node(tree.state().node_by_id(id).unwrap().data());
+        builder.set_children(children);
+        builder.build()
+    }
+
+    #[test]
+    fn capture_mutate_and_restore() {
+        let mut tree = test_tree();
+        let snapshot = tree
+            .state()
+            .extract_subtree(PARAGRAPH_1_IGNORED_ID)
+            .unwrap();
+        assert_eq!(PARAGRAPH_1_IGNORED_ID, snapshot.root_id());
+        assert_eq!(
+            vec![
+                PARAGRAPH_1_IGNORED_ID,
+                BUTTON_1_0_HIDDEN_ID,
+                CONTAINER_1_0_0_HIDDEN_ID,
+                LABEL_1_1_ID,
+                BUTTON_1_2_HIDDEN_ID,
+                CONTAINER_1_2_0_HIDDEN_ID,
+            ],
+            snapshot
+                .nodes()
+                .iter()
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>()
+        );
+
+        let new_label_id = NodeId(100);
+        let label = {
+            let mut builder = NodeBuilder::new(Role::Label);
+            builder.set_name("renamed");
+            builder.build()
+        };
+        let new_label = {
+            let mut builder = NodeBuilder::new(Role::Label);
+            builder.set_name("new");
+            builder.build()
+        };
+        apply(&mut tree, |tree| {
+            vec![
+                (
+                    PARAGRAPH_1_IGNORED_ID,
+                    with_children(
+                        PARAGRAPH_1_IGNORED_ID,
+                        tree,
+                        vec![LABEL_1_1_ID, new_label_id],
+                    ),
+                ),
+                (LABEL_1_1_ID, label),
+                (new_label_id, new_label),
+            ]
+        });
+        assert!(!tree.state().has_node(BUTTON_1_0_HIDDEN_ID));
+
+        let update = snapshot.to_restoring_update(tree.state()).unwrap();
+        tree.update(update);
+        assert!(!tree.state().has_node(new_label_id));
+        assert_eq!(
+            Some(snapshot),
+            tree.state().extract_subtree(PARAGRAPH_1_IGNORED_ID)
+        );
+    }
+
+    #[test]
+    fn restore_removed_subtree() {
+        let mut tree = test_tree();
+        let original = tree.state().serialize();
+        let snapshot = tree.state().extract_subtree(PARAGRAPH_2_ID).unwrap();
+        apply(&mut tree, |tree| {
+            vec![(
+                ROOT_ID,
+                with_children(
+                    ROOT_ID,
+                    tree,
+                    vec![
+                        PARAGRAPH_0_ID,
+                        PARAGRAPH_1_IGNORED_ID,
+                        PARAGRAPH_3_IGNORED_ID,
+                    ],
+                ),
+            )]
+        });
+        assert!(!tree.state().has_node(LABEL_2_0_ID));
+
+        let update = snapshot.to_restoring_update(tree.state()).unwrap();
+        tree.update(update);
+        assert_eq!(original, tree.state().serialize());
+    }
+
+    #[test]
+    fn restore_whole_tree() {
+        let mut tree = test_tree();
+        let original = tree.state().serialize();
+        let snapshot = tree.state().extract_subtree(ROOT_ID).unwrap();
+        apply(&mut tree, |tree| {
+            vec![(ROOT_ID, with_children(ROOT_ID, tree, vec![PARAGRAPH_0_ID]))]
+        });
+        tree.update(snapshot.to_restoring_update(tree.state()).unwrap());
+        assert_eq!(original, tree.state().serialize());
+
+        let mut other_root = test_tree();
+        other_root.update(
+            TreeUpdateBuilder::new()
+                .tree_root(NodeId(200))
+                .node(NodeId(200), NodeBuilder::new(Role::Window).build())
+                .build(),
+        );
+        assert_eq!(
+            Err(RestoreError::RootReplaced(NodeId(200))),
+            snapshot.to_restoring_update(other_root.state())
+        );
+    }
+
+    #[test]
+    fn restore_conflicts() {
+        let mut tree = test_tree();
+        let snapshot = tree.state().extract_subtree(PARAGRAPH_2_ID).unwrap();
+        // Move the label out of the paragraph, into another one.
+        apply(&mut tree, |tree| {
+            vec![
+                (PARAGRAPH_2_ID, with_children(PARAGRAPH_2_ID, tree, vec![])),
+                (
+                    PARAGRAPH_0_ID,
+                    with_children(
+                        PARAGRAPH_0_ID,
+                        tree,
+                        vec![LABEL_0_0_IGNORED_ID, LABEL_2_0_ID],
+                    ),
+                ),
+            ]
+        });
+        assert_eq!(
+            Err(RestoreError::IdInUse(LABEL_2_0_ID)),
+            snapshot.to_restoring_update(tree.state())
+        );
+
+        let mut tree = test_tree();
+        let snapshot = tree.state().extract_subtree(LABEL_2_0_ID).unwrap();
+        apply(&mut tree, |tree| {
+            vec![(
+                ROOT_ID,
+                with_children(ROOT_ID, tree, vec![PARAGRAPH_0_ID, PARAGRAPH_1_IGNORED_ID]),
+            )]
+        });
+        assert_eq!(
+            Err(RestoreError::MissingParent(PARAGRAPH_2_ID)),
+            snapshot.to_restoring_update(tree.state())
+        );
+    }
+
+    #[test]
+    fn missing_root() {
+        let tree = test_tree();
+        assert!(tree.state().extract_subtree(NodeId(1000)).is_none());
+    }
+}
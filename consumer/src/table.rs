@@ -0,0 +1,391 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::Role;
+
+use crate::node::Node;
+
+// Row and column indices are zero-based. When a row or cell doesn't
+// specify its index explicitly, it's computed from the structure
+// of the tree. The computed column index of a cell doesn't take into
+// account cells from previous rows that span multiple rows, so providers
+// should set explicit indices on tables that use row spans.
+
+impl<'a> Node<'a> {
+    pub fn is_table(&self) -> bool {
+        matches!(
+            self.role(),
+            Role::Table | Role::Grid | Role::TreeGrid | Role::ListGrid
+        )
+    }
+
+    pub fn is_table_row(&self) -> bool {
+        self.role() == Role::Row
+    }
+
+    pub fn is_table_cell(&self) -> bool {
+        matches!(
+            self.role(),
+            Role::Cell | Role::ColumnHeader | Role::RowHeader
+        )
+    }
+
+    /// Returns the nearest table, grid, or tree grid that contains this node.
+    pub fn containing_table(&self) -> Option<Node<'a>> {
+        let mut current = self.parent();
+        while let Some(node) = current {
+            if node.is_table() {
+                return Some(node);
+            }
+            current = node.parent();
+        }
+        None
+    }
+
+    fn collect_table_rows(&self, rows: &mut Vec<Node<'a>>) {
+        for child in self.children() {
+            if child.is_hidden() {
+                continue;
+            }
+            if child.is_table_row() {
+                rows.push(child);
+            } else if !child.is_table() && !child.is_table_cell() {
+                child.collect_table_rows(rows);
+            }
+        }
+    }
+
+    /// Returns the rows of this table, including rows inside row groups,
+    /// but not rows of nested tables.
+    pub fn table_rows(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + ExactSizeIterator<Item = Node<'a>> {
+        let mut rows = Vec::new();
+        if self.is_table() {
+            self.collect_table_rows(&mut rows);
+        }
+        rows.into_iter()
+    }
+
+    /// Returns the cells of this row.
+    pub fn table_row_cells(&self) -> impl DoubleEndedIterator<Item = Node<'a>> + 'a {
+        let is_row = self.is_table_row();
+        self.children()
+            .filter(move |child| is_row && child.is_table_cell() && !child.is_hidden())
+    }
+
+    /// Returns all of the cells in this table, in row order.
+    pub fn table_cells(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        self.table_rows().flat_map(|row| row.table_row_cells())
+    }
+
+    pub fn table_row_count(&self) -> Option<usize> {
+        if !self.is_table() {
+            return None;
+        }
+        self.data()
+            .row_count()
+            .or_else(|| Some(self.table_rows().len()))
+    }
+
+    pub fn table_column_count(&self) -> Option<usize> {
+        if !self.is_table() {
+            return None;
+        }
+        self.data().column_count().or_else(|| {
+            Some(
+                self.table_cells()
+                    .filter_map(|cell| {
+                        cell.table_column_index()
+                            .map(|index| index + cell.table_column_span())
+                    })
+                    .max()
+                    .unwrap_or(0),
+            )
+        })
+    }
+
+    /// Returns the index of this row or cell within its table.
+    pub fn table_row_index(&self) -> Option<usize> {
+        if let Some(index) = self.data().row_index() {
+            return Some(index);
+        }
+        if self.is_table_row() {
+            let id = self.id();
+            return self
+                .containing_table()?
+                .table_rows()
+                .position(|row| row.id() == id);
+        }
+        if self.is_table_cell() {
+            return self.parent().filter(Node::is_table_row)?.table_row_index();
+        }
+        None
+    }
+
+    /// Returns the index of the first column that this cell occupies.
+    pub fn table_column_index(&self) -> Option<usize> {
+        if let Some(index) = self.data().column_index() {
+            return Some(index);
+        }
+        if !self.is_table_cell() {
+            return None;
+        }
+        let row = self.parent().filter(Node::is_table_row)?;
+        let mut column = 0;
+        for cell in row.table_row_cells() {
+            if cell.id() == self.id() {
+                return Some(column);
+            }
+            column = cell.data().column_index().unwrap_or(column) + cell.table_column_span();
+        }
+        None
+    }
+
+    pub fn table_row_span(&self) -> usize {
+        self.data().row_span().unwrap_or(1)
+    }
+
+    pub fn table_column_span(&self) -> usize {
+        self.data().column_span().unwrap_or(1)
+    }
+
+    /// Returns the cell of this table that occupies the given row and column,
+    /// taking row and column spans into account.
+    pub fn table_cell_at(&self, row: usize, column: usize) -> Option<Node<'a>> {
+        // Compute each row's index and each cell's column index as we go,
+        // the same way as `table_row_index` and `table_column_index`,
+        // rather than searching the table again for every cell.
+        for (position, table_row) in self.table_rows().enumerate() {
+            let row_index = table_row.data().row_index().unwrap_or(position);
+            let mut next_column_index = 0;
+            for cell in table_row.table_row_cells() {
+                let cell_row_index = cell.data().row_index().unwrap_or(row_index);
+                let column_index = cell.data().column_index().unwrap_or(next_column_index);
+                if (cell_row_index..cell_row_index + cell.table_row_span()).contains(&row)
+                    && (column_index..column_index + cell.table_column_span()).contains(&column)
+                {
+                    return Some(cell);
+                }
+                next_column_index = column_index + cell.table_column_span();
+            }
+        }
+        None
+    }
+
+    pub fn table_row_headers(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        self.table_cells()
+            .filter(|cell| cell.role() == Role::RowHeader)
+    }
+
+    pub fn table_column_headers(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        self.table_cells()
+            .filter(|cell| cell.role() == Role::ColumnHeader)
+    }
+
+    /// Returns the row headers of the table that apply to this cell.
+    pub fn table_cell_row_headers(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        let id = self.id();
+        let rows = self
+            .table_row_index()
+            .map(|index| index..index + self.table_row_span());
+        self.containing_table()
+            .filter(|_| self.is_table_cell())
+            .into_iter()
+            .flat_map(|table| table.table_row_headers())
+            .filter(move |header| {
+                header.id() != id
+                    && rows.as_ref().is_some_and(|rows| {
+                        header.table_row_index().is_some_and(|index| {
+                            index < rows.end && rows.start < index + header.table_row_span()
+                        })
+                    })
+            })
+    }
+
+    /// Returns the column headers of the table that apply to this cell.
+    pub fn table_cell_column_headers(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        let id = self.id();
+        let columns = self
+            .table_column_index()
+            .map(|index| index..index + self.table_column_span());
+        self.containing_table()
+            .filter(|_| self.is_table_cell())
+            .into_iter()
+            .flat_map(|table| table.table_column_headers())
+            .filter(move |header| {
+                header.id() != id
+                    && columns.as_ref().is_some_and(|columns| {
+                        header.table_column_index().is_some_and(|index| {
+                            index < columns.end
+                                && columns.start < index + header.table_column_span()
+                        })
+                    })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{NodeBuilder, NodeId, Role, TreeUpdateBuilder};
+
+    use crate::tests::node_with_children;
+
+    const TABLE_ID: NodeId = NodeId(0);
+    const HEADER_ROW_ID: NodeId = NodeId(1);
+    const CORNER_ID: NodeId = NodeId(2);
+    const NAME_HEADER_ID: NodeId = NodeId(3);
+    const SCORE_HEADER_ID: NodeId = NodeId(4);
+    const BODY_ID: NodeId = NodeId(5);
+    const ROW_1_ID: NodeId = NodeId(6);
+    const ROW_1_HEADER_ID: NodeId = NodeId(7);
+    const ROW_1_WIDE_CELL_ID: NodeId = NodeId(8);
+    const ROW_2_ID: NodeId = NodeId(9);
+    const ROW_2_HEADER_ID: NodeId = NodeId(10);
+    const ROW_2_NAME_ID: NodeId = NodeId(11);
+    const ROW_2_SCORE_ID: NodeId = NodeId(12);
+
+    fn test_tree() -> crate::Tree {
+        let wide_cell = {
+            let mut builder = NodeBuilder::new(Role::Cell);
+            builder.set_column_span(2);
+            builder.build()
+        };
+        let update = TreeUpdateBuilder::new()
+            .tree_root(TABLE_ID)
+            .node(
+                TABLE_ID,
+                node_with_children(Role::Table, vec![HEADER_ROW_ID, BODY_ID]),
+            )
+            .node(
+                HEADER_ROW_ID,
+                node_with_children(Role::Row, vec![CORNER_ID, NAME_HEADER_ID, SCORE_HEADER_ID]),
+            )
+            .node(CORNER_ID, node_with_children(Role::Cell, vec![]))
+            .node(
+                NAME_HEADER_ID,
+                node_with_children(Role::ColumnHeader, vec![]),
+            )
+            .node(
+                SCORE_HEADER_ID,
+                node_with_children(Role::ColumnHeader, vec![]),
+            )
+            .node(
+                BODY_ID,
+                node_with_children(Role::RowGroup, vec![ROW_1_ID, ROW_2_ID]),
+            )
+            .node(
+                ROW_1_ID,
+                node_with_children(Role::Row, vec![ROW_1_HEADER_ID, ROW_1_WIDE_CELL_ID]),
+            )
+            .node(ROW_1_HEADER_ID, node_with_children(Role::RowHeader, vec![]))
+            .node(ROW_1_WIDE_CELL_ID, wide_cell)
+            .node(
+                ROW_2_ID,
+                node_with_children(
+                    Role::Row,
+                    vec![ROW_2_HEADER_ID, ROW_2_NAME_ID, ROW_2_SCORE_ID],
+                ),
+            )
+            .node(ROW_2_HEADER_ID, node_with_children(Role::RowHeader, vec![]))
+            .node(ROW_2_NAME_ID, node_with_children(Role::Cell, vec![]))
+            .node(ROW_2_SCORE_ID, node_with_children(Role::Cell, vec![]))
+            .build();
+        crate::Tree::new(update, false)
+    }
+
+    #[test]
+    fn dimensions() {
+        let tree = test_tree();
+        let table = tree.state().root();
+        assert!(table.is_table());
+        assert_eq!(Some(3), table.table_row_count());
+        assert_eq!(Some(3), table.table_column_count());
+        assert_eq!(
+            vec![HEADER_ROW_ID, ROW_1_ID, ROW_2_ID],
+            table.table_rows().map(|row| row.id()).collect::<Vec<_>>()
+        );
+        let body = tree.state().node_by_id(BODY_ID).unwrap();
+        assert_eq!(None, body.table_row_count());
+    }
+
+    #[test]
+    fn cell_positions() {
+        let tree = test_tree();
+        let state = tree.state();
+        let position = |id| {
+            let cell = state.node_by_id(id).unwrap();
+            (
+                cell.table_row_index(),
+                cell.table_column_index(),
+                cell.table_row_span(),
+                cell.table_column_span(),
+            )
+        };
+        assert_eq!((Some(0), Some(2), 1, 1), position(SCORE_HEADER_ID));
+        assert_eq!((Some(1), Some(1), 1, 2), position(ROW_1_WIDE_CELL_ID));
+        assert_eq!((Some(2), Some(2), 1, 1), position(ROW_2_SCORE_ID));
+        assert_eq!((Some(2), None, 1, 1), position(ROW_2_ID));
+        assert_eq!(
+            Some(TABLE_ID),
+            state
+                .node_by_id(ROW_2_SCORE_ID)
+                .unwrap()
+                .containing_table()
+                .map(|table| table.id())
+        );
+    }
+
+    #[test]
+    fn cell_lookup() {
+        let tree = test_tree();
+        let table = tree.state().root();
+        let cell_id = |row, column| table.table_cell_at(row, column).map(|cell| cell.id());
+        assert_eq!(Some(CORNER_ID), cell_id(0, 0));
+        assert_eq!(Some(ROW_1_WIDE_CELL_ID), cell_id(1, 1));
+        assert_eq!(Some(ROW_1_WIDE_CELL_ID), cell_id(1, 2));
+        assert_eq!(Some(ROW_2_NAME_ID), cell_id(2, 1));
+        assert_eq!(None, cell_id(3, 0));
+        assert_eq!(None, cell_id(0, 3));
+    }
+
+    #[test]
+    fn headers() {
+        let tree = test_tree();
+        let state = tree.state();
+        let table = state.root();
+        assert_eq!(
+            vec![ROW_1_HEADER_ID, ROW_2_HEADER_ID],
+            table
+                .table_row_headers()
+                .map(|header| header.id())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![NAME_HEADER_ID, SCORE_HEADER_ID],
+            table
+                .table_column_headers()
+                .map(|header| header.id())
+                .collect::<Vec<_>>()
+        );
+        let wide_cell = state.node_by_id(ROW_1_WIDE_CELL_ID).unwrap();
+        assert_eq!(
+            vec![ROW_1_HEADER_ID],
+            wide_cell
+                .table_cell_row_headers()
+                .map(|header| header.id())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![NAME_HEADER_ID, SCORE_HEADER_ID],
+            wide_cell
+                .table_cell_column_headers()
+                .map(|header| header.id())
+                .collect::<Vec<_>>()
+        );
+        let score_header = state.node_by_id(SCORE_HEADER_ID).unwrap();
+        assert_eq!(0, score_header.table_cell_column_headers().count());
+    }
+}
@@ -3,14 +3,21 @@
 // the LICENSE-APACHE file) or the MIT license (found in
 // the LICENSE-MIT file), at your option.
 
-use accesskit::{Node as NodeData, NodeId, Tree as TreeData, TreeUpdate};
+use accesskit::{
+    Affine, Node as NodeData, NodeBuilder, NodeId, Point, Role, Tree as TreeData, TreeUpdate,
+};
 use immutable_chunkmap::map::MapM as ChunkMap;
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
+    iter::FusedIterator,
+    sync::{Arc, Mutex},
 };
 
-use crate::node::{Node, NodeState, ParentAndIndex};
+use crate::{
+    filters::{apply_filter, FilterResult},
+    iterators::{FilteredSubtreeNodes, SubtreeNodes},
+    node::{Node, NodeState, ParentAndIndex},
+};
 
 #[derive(Clone)]
 pub struct State {
@@ -18,6 +25,126 @@ pub struct State {
     pub(crate) data: TreeData,
     pub(crate) focus: NodeId,
     is_host_focused: bool,
+    root_to_screen_transform: Affine,
+    reverse_relations: ChunkMap<NodeId, ReverseRelations>,
+    role_index: ChunkMap<Role, Arc<HashSet<NodeId>>>,
+    /// The IDs in each set of the role index, sorted into document order
+    /// when first needed. Every update replaces this cache, so a clone of
+    /// the state from before the update keeps a valid cache of its own.
+    role_order: Arc<Mutex<HashMap<Role, Arc<[NodeId]>>>>,
+    load_handler: Option<Arc<dyn Fn(NodeId) + Send + Sync>>,
+    requested_loads: Arc<Mutex<HashSet<NodeId>>>,
+}
+
+/// The nodes that refer to a given node through each relation property.
+#[derive(Clone, Default)]
+pub(crate) struct ReverseRelations {
+    pub(crate) labelled_by: Vec<NodeId>,
+    pub(crate) controls: Vec<NodeId>,
+    pub(crate) described_by: Vec<NodeId>,
+}
+
+impl ReverseRelations {
+    fn is_empty(&self) -> bool {
+        self.labelled_by.is_empty() && self.controls.is_empty() && self.described_by.is_empty()
+    }
+}
+
+static EMPTY_REVERSE_RELATIONS: ReverseRelations = ReverseRelations {
+    labelled_by: Vec::new(),
+    controls: Vec::new(),
+    described_by: Vec::new(),
+};
+
+/// The maximum number of active descendant relations that
+/// [`State::effective_focus`] follows from the focused node.
+const MAX_ACTIVE_DESCENDANT_DEPTH: usize = 8;
+
+fn update_reverse_relations(
+    index: &mut ChunkMap<NodeId, ReverseRelations>,
+    source: NodeId,
+    old_data: Option<&NodeData>,
+    new_data: Option<&NodeData>,
+) {
+    fn update_relation(
+        index: &mut ChunkMap<NodeId, ReverseRelations>,
+        source: NodeId,
+        old_targets: &[NodeId],
+        new_targets: &[NodeId],
+        sources: fn(&mut ReverseRelations) -> &mut Vec<NodeId>,
+    ) {
+        for target in old_targets {
+            if new_targets.contains(target) {
+                continue;
+            }
+            if let Some(relations) = index.get_mut_cow(target) {
+                sources(relations).retain(|id| *id != source);
+                if relations.is_empty() {
+                    index.remove_cow(target);
+                }
+            }
+        }
+        for target in new_targets {
+            if old_targets.contains(target) {
+                continue;
+            }
+            if let Some(relations) = index.get_mut_cow(target) {
+                sources(relations).push(source);
+            } else {
+                let mut relations = ReverseRelations::default();
+                sources(&mut relations).push(source);
+                index.insert_cow(*target, relations);
+            }
+        }
+    }
+
+    update_relation(
+        index,
+        source,
+        old_data.map_or(&[], |data| data.labelled_by()),
+        new_data.map_or(&[], |data| data.labelled_by()),
+        |relations| &mut relations.labelled_by,
+    );
+    update_relation(
+        index,
+        source,
+        old_data.map_or(&[], |data| data.controls()),
+        new_data.map_or(&[], |data| data.controls()),
+        |relations| &mut relations.controls,
+    );
+    update_relation(
+        index,
+        source,
+        old_data.map_or(&[], |data| data.described_by()),
+        new_data.map_or(&[], |data| data.described_by()),
+        |relations| &mut relations.described_by,
+    );
+}
+
+fn update_role_index(
+    index: &mut ChunkMap<Role, Arc<HashSet<NodeId>>>,
+    id: NodeId,
+    old_role: Option<Role>,
+    new_role: Option<Role>,
+) {
+    if old_role == new_role {
+        return;
+    }
+    if let Some(role) = old_role {
+        if let Some(ids) = index.get_mut_cow(&role) {
+            Arc::make_mut(ids).remove(&id);
+            if ids.is_empty() {
+                index.remove_cow(&role);
+            }
+        }
+    }
+    if let Some(role) = new_role {
+        if let Some(ids) = index.get_mut_cow(&role) {
+            Arc::make_mut(ids).insert(id);
+        } else {
+            index.insert_cow(role, Arc::new(HashSet::from([id])));
+        }
+    }
 }
 
 #[derive(Default)]
@@ -43,6 +170,15 @@ impl State {
         is_host_focused: bool,
         mut changes: Option<&mut InternalChanges>,
     ) {
+        self.role_order = Arc::new(Mutex::new(HashMap::new()));
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "tree_update",
+            nodes = update.nodes.len(),
+            includes_tree = update.tree.is_some(),
+        )
+        .entered();
+
         let mut orphans = HashSet::new();
 
         if let Some(tree) = update.tree {
@@ -58,11 +194,17 @@ impl State {
 
         fn add_node(
             nodes: &mut ChunkMap<NodeId, NodeState>,
+            reverse_relations: &mut ChunkMap<NodeId, ReverseRelations>,
+            role_index: &mut ChunkMap<Role, Arc<HashSet<NodeId>>>,
             changes: &mut Option<&mut InternalChanges>,
             parent_and_index: Option<ParentAndIndex>,
             id: NodeId,
             data: NodeData,
         ) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(node = id.0, role = ?data.role(), "node added");
+            update_reverse_relations(reverse_relations, id, None, Some(&data));
+            update_role_index(role_index, id, None, Some(data.role()));
             let state = NodeState {
                 parent_and_index,
                 data: Arc::new(data),
@@ -88,11 +230,25 @@ impl State {
                 let parent_and_index = ParentAndIndex(node_id, child_index);
                 if let Some(child_state) = self.nodes.get_mut_cow(child_id) {
                     if child_state.parent_and_index != Some(parent_and_index) {
+                        #[cfg(feature = "tracing")]
+                        if let Some(ParentAndIndex(old_parent_id, _)) = child_state.parent_and_index
+                        {
+                            if old_parent_id != node_id {
+                                tracing::debug!(
+                                    node = child_id.0,
+                                    old_parent = old_parent_id.0,
+                                    new_parent = node_id.0,
+                                    "node reparented"
+                                );
+                            }
+                        }
                         child_state.parent_and_index = Some(parent_and_index);
                     }
                 } else if let Some(child_data) = pending_nodes.remove(child_id) {
                     add_node(
                         &mut self.nodes,
+                        &mut self.reverse_relations,
+                        &mut self.role_index,
                         &mut changes,
                         Some(parent_and_index),
                         *child_id,
@@ -114,6 +270,18 @@ impl State {
                     }
                 }
                 if *node_state.data != node_data {
+                    update_reverse_relations(
+                        &mut self.reverse_relations,
+                        node_id,
+                        Some(&node_state.data),
+                        Some(&node_data),
+                    );
+                    update_role_index(
+                        &mut self.role_index,
+                        node_id,
+                        Some(node_state.data.role()),
+                        Some(node_data.role()),
+                    );
                     node_state.data = Arc::new(node_data);
                     if let Some(changes) = &mut changes {
                         changes.updated_node_ids.insert(node_id);
@@ -122,13 +290,23 @@ impl State {
             } else if let Some(parent_and_index) = pending_children.remove(&node_id) {
                 add_node(
                     &mut self.nodes,
+                    &mut self.reverse_relations,
+                    &mut self.role_index,
                     &mut changes,
                     Some(parent_and_index),
                     node_id,
                     node_data,
                 );
             } else if node_id == root {
-                add_node(&mut self.nodes, &mut changes, None, node_id, node_data);
+                add_node(
+                    &mut self.nodes,
+                    &mut self.reverse_relations,
+                    &mut self.role_index,
+                    &mut changes,
+                    None,
+                    node_id,
+                    node_data,
+                );
             } else {
                 pending_nodes.insert(node_id, node_data);
             }
@@ -147,6 +325,20 @@ impl State {
         if !orphans.is_empty() {
             let mut to_remove = HashSet::new();
 
+            // A node that was dropped by its old parent may have been
+            // moved to a new parent by the same update, regardless of
+            // the order in which the two parents appear in the update.
+            fn is_attached(nodes: &ChunkMap<NodeId, NodeState>, id: NodeId) -> bool {
+                nodes
+                    .get(&id)
+                    .and_then(|node| node.parent_and_index.as_ref())
+                    .is_some_and(|ParentAndIndex(parent_id, index)| {
+                        nodes
+                            .get(parent_id)
+                            .is_some_and(|parent| parent.data.children().get(*index) == Some(&id))
+                    })
+            }
+
             fn traverse_orphan(
                 nodes: &ChunkMap<NodeId, NodeState>,
                 to_remove: &mut HashSet<NodeId>,
@@ -155,16 +347,34 @@ impl State {
                 to_remove.insert(id);
                 let node = nodes.get(&id).unwrap();
                 for child_id in node.data.children().iter() {
-                    traverse_orphan(nodes, to_remove, *child_id);
+                    let child = nodes.get(child_id).unwrap();
+                    if child
+                        .parent_and_index
+                        .as_ref()
+                        .map_or(true, |ParentAndIndex(parent_id, _)| *parent_id == id)
+                    {
+                        traverse_orphan(nodes, to_remove, *child_id);
+                    }
                 }
             }
 
             for id in orphans {
-                traverse_orphan(&self.nodes, &mut to_remove, id);
+                if !is_attached(&self.nodes, id) {
+                    traverse_orphan(&self.nodes, &mut to_remove, id);
+                }
             }
 
             for id in to_remove {
-                if self.nodes.remove_cow(&id).is_some() {
+                if let Some(node_state) = self.nodes.remove_cow(&id) {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(node = id.0, role = ?node_state.data.role(), "node removed");
+                    update_reverse_relations(
+                        &mut self.reverse_relations,
+                        id,
+                        Some(&node_state.data),
+                        None,
+                    );
+                    update_role_index(&mut self.role_index, id, Some(node_state.data.role()), None);
                     if let Some(changes) = &mut changes {
                         changes.removed_node_ids.insert(id);
                     }
@@ -172,6 +382,14 @@ impl State {
             }
         }
 
+        // Once a node's children have been loaded, or the node is gone,
+        // a later request to load its children should be allowed again.
+        self.requested_loads.lock().unwrap().retain(|id| {
+            self.nodes
+                .get(id)
+                .is_some_and(|node| node.has_unloaded_children())
+        });
+
         self.validate_global();
     }
 
@@ -210,10 +428,155 @@ impl State {
         }
     }
 
+    /// Returns an update that transforms this state into the one described
+    /// by the given full update, such as one produced by [`State::serialize`]
+    /// or by a toolkit that rebuilds its whole tree every frame. Nodes
+    /// whose data is unchanged are left out, even if they're in `new_full`,
+    /// and the tree data is only included if it differs. Structural changes
+    /// are carried by the parents whose lists of children have changed.
+    pub fn diff(&self, new_full: &TreeUpdate) -> TreeUpdate {
+        let nodes = new_full
+            .nodes
+            .iter()
+            .filter(|(id, data)| {
+                self.nodes
+                    .get(id)
+                    .map_or(true, |node_state| *node_state.data != *data)
+            })
+            .cloned()
+            .collect();
+        let tree = new_full
+            .tree
+            .as_ref()
+            .filter(|tree| **tree != self.data)
+            .cloned();
+        TreeUpdate {
+            nodes,
+            tree,
+            focus: new_full.focus,
+        }
+    }
+
+    /// Notify the load handler, if any, that the children of the given node
+    /// are needed. The handler is only called the first time this happens
+    /// for a given node while its children remain unloaded.
+    pub(crate) fn request_children(&self, id: NodeId) {
+        let Some(handler) = &self.load_handler else {
+            return;
+        };
+        let is_new_request = self.requested_loads.lock().unwrap().insert(id);
+        if is_new_request {
+            handler(id);
+        }
+    }
+
+    pub(crate) fn reverse_relations(&self, id: NodeId) -> &ReverseRelations {
+        self.reverse_relations
+            .get(&id)
+            .unwrap_or(&EMPTY_REVERSE_RELATIONS)
+    }
+
+    /// Returns the nodes with the given role, in document order.
+    /// This uses an index that is maintained as the tree is updated,
+    /// so its cost depends on the number of nodes with the role,
+    /// not the size of the tree. The nodes are sorted the first time
+    /// they're needed after each update.
+    pub fn nodes_with_role(
+        &self,
+        role: Role,
+    ) -> impl DoubleEndedIterator<Item = Node<'_>> + ExactSizeIterator<Item = Node<'_>> {
+        let ids = self.role_ids_in_document_order(role);
+        (0..ids.len()).map(move |i| self.node_by_id(ids[i]).unwrap())
+    }
+
+    pub(crate) fn role_ids_in_document_order(&self, role: Role) -> Arc<[NodeId]> {
+        let mut role_order = self.role_order.lock().unwrap();
+        if let Some(ids) = role_order.get(&role) {
+            return Arc::clone(ids);
+        }
+        let mut nodes = self.role_index.get(&role).map_or_else(Vec::new, |ids| {
+            ids.iter()
+                .map(|id| (self.node_by_id(*id).unwrap().index_path(), *id))
+                .collect::<Vec<_>>()
+        });
+        nodes.sort_unstable();
+        let ids = nodes
+            .into_iter()
+            .map(|(_, id)| id)
+            .collect::<Arc<[NodeId]>>();
+        role_order.insert(role, Arc::clone(&ids));
+        ids
+    }
+
+    /// Returns the given node and all of its descendants, in pre-order
+    /// depth-first order. The traversal uses an explicit stack rather than
+    /// recursion, so it can handle arbitrarily deep trees. If the node
+    /// isn't in the tree, the iterator is empty.
+    pub fn iter_subtree(&self, root: NodeId) -> impl FusedIterator<Item = Node<'_>> {
+        SubtreeNodes::new(self, root)
+    }
+
+    /// Like [`State::iter_subtree`], but only yields the nodes that
+    /// the filter includes, skipping excluded subtrees entirely.
+    pub fn iter_subtree_filtered<'a>(
+        &'a self,
+        root: NodeId,
+        filter: impl Fn(&Node) -> FilterResult + 'a,
+    ) -> impl FusedIterator<Item = Node<'a>> + 'a {
+        FilteredSubtreeNodes::new(self, root, filter)
+    }
+
+    /// Returns the nodes that the filter includes in paint order, that is,
+    /// in the order in which they're drawn, from back to front. Each node
+    /// comes before its descendants, and siblings are ordered as described
+    /// for [`Node::children_in_paint_order`]. This is useful for drawing
+    /// overlays that reflect the stacking of the nodes.
+    pub fn nodes_in_paint_order<'a>(
+        &'a self,
+        filter: impl Fn(&Node) -> FilterResult + 'a,
+    ) -> impl FusedIterator<Item = Node<'a>> + 'a {
+        let mut stack = vec![self.root()];
+        std::iter::from_fn(move || {
+            while let Some(node) = stack.pop() {
+                let result = apply_filter(&filter, &node);
+                if result == FilterResult::ExcludeSubtree {
+                    continue;
+                }
+                stack.extend(node.paint_ordered_children().into_iter().rev());
+                if result == FilterResult::Include {
+                    return Some(node);
+                }
+            }
+            None
+        })
+        .fuse()
+    }
+
+    /// Returns the deepest node that the filter includes at the given point
+    /// in the root node's coordinate space, taking paint order into account.
+    /// See [`Node::node_at_point`].
+    pub fn top_most_at_point(
+        &self,
+        point: Point,
+        filter: &impl Fn(&Node) -> FilterResult,
+    ) -> Option<Node<'_>> {
+        self.root().node_at_point(point, filter)
+    }
+
     pub fn has_node(&self, id: NodeId) -> bool {
         self.nodes.get(&id).is_some()
     }
 
+    /// Looks up a node by ID. The nodes are stored in a persistent ordered
+    /// map, so this takes logarithmic time in the number of nodes.
+    /// A hash map would make lookups faster, but then cloning the state,
+    /// which every update does, would take time proportional to the number
+    /// of nodes rather than constant time.
+    ///
+    /// Applying an update doesn't copy or move the data of nodes that
+    /// the update doesn't include; a clone of the previous state and the
+    /// updated state share it, so the addresses of those nodes' properties
+    /// stay the same across the update.
     pub fn node_by_id(&self, id: NodeId) -> Option<Node<'_>> {
         self.nodes.get(&id).map(|node_state| Node {
             tree_state: self,
@@ -234,6 +597,35 @@ impl State {
         self.is_host_focused
     }
 
+    /// Returns the transform from the root node's coordinate space to
+    /// screen coordinates, as set by [`Tree::set_root_to_screen_transform`].
+    /// This is the identity transform by default.
+    pub fn root_to_screen_transform(&self) -> Affine {
+        self.root_to_screen_transform
+    }
+
+    /// Returns the node that assistive technologies should describe as
+    /// focused. This starts with the node that has input focus, as returned
+    /// by [`State::focus`], and follows its [active descendant](Node::active_descendant),
+    /// and in turn that node's active descendant, as long as the filter
+    /// includes it. The chain is only followed for a few steps, and stops
+    /// if it would revisit a node.
+    pub fn effective_focus(&self, filter: impl Fn(&Node) -> FilterResult) -> Option<Node<'_>> {
+        let mut node = self.focus()?;
+        let mut visited = vec![node.id()];
+        while visited.len() <= MAX_ACTIVE_DESCENDANT_DEPTH {
+            let Some(active) = node.active_descendant() else {
+                break;
+            };
+            if visited.contains(&active.id()) || filter(&active) != FilterResult::Include {
+                break;
+            }
+            visited.push(active.id());
+            node = active;
+        }
+        Some(node)
+    }
+
     pub fn focus_id(&self) -> Option<NodeId> {
         self.is_host_focused.then_some(self.focus)
     }
@@ -242,6 +634,18 @@ impl State {
         self.focus_id().map(|id| self.node_by_id(id).unwrap())
     }
 
+    /// Returns the title of the window or document that this tree
+    /// represents. If the root node is a window or document, this is its
+    /// computed [name](Node::name), which may come from the nodes it's
+    /// [labelled by](Node::labelled_by); otherwise the tree has no title.
+    pub fn title(&self) -> Option<String> {
+        let root = self.root();
+        match root.role() {
+            Role::Window | Role::Document | Role::RootWebArea => root.name(),
+            _ => None,
+        }
+    }
+
     pub fn app_name(&self) -> Option<String> {
         self.data.app_name.clone()
     }
@@ -253,6 +657,13 @@ impl State {
     pub fn toolkit_version(&self) -> Option<String> {
         self.data.toolkit_version.clone()
     }
+
+    /// Returns a human-readable representation of the whole tree.
+    /// See [`debug_print_tree`](crate::debug_print_tree).
+    #[cfg(feature = "debug")]
+    pub fn debug_print(&self) -> String {
+        crate::debug::debug_print_tree(self)
+    }
 }
 
 pub trait ChangeHandler {
@@ -260,8 +671,49 @@ pub trait ChangeHandler {
     fn node_updated(&mut self, old_node: &Node, new_node: &Node);
     fn focus_moved(&mut self, old_node: Option<&Node>, new_node: Option<&Node>);
     fn node_removed(&mut self, node: &Node);
+
+    /// Called after [`ChangeHandler::node_updated`] when the node's
+    /// busy state changed, so adapters can announce that its contents
+    /// are loading or have finished loading. See [`Node::is_busy`].
+    fn busy_state_changed(&mut self, _node: &Node) {}
+
+    /// Called last when the tree's [title](State::title) changed,
+    /// whether because the root node was renamed, a node it's labelled by
+    /// was renamed, or its labelling relation now targets other nodes.
+    fn title_changed(&mut self, _title: Option<&str>) {}
+
+    /// Called after [`ChangeHandler::node_updated`] when the node with
+    /// input focus didn't change, but its
+    /// [active descendant](Node::active_descendant) did. A change of input
+    /// focus is reported through [`ChangeHandler::focus_moved`] instead.
+    fn active_descendant_changed(
+        &mut self,
+        _focus: &Node,
+        _old_node: Option<&Node>,
+        _new_node: Option<&Node>,
+    ) {
+    }
+}
+
+/// The reason that [`Tree::replace_subtree`] couldn't replace a subtree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplaceSubtreeError {
+    /// The root of the subtree to replace isn't in the tree.
+    MissingRoot(NodeId),
 }
 
+impl std::fmt::Display for ReplaceSubtreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingRoot(id) => {
+                write!(f, "node #{} is not in the tree", id.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplaceSubtreeError {}
+
 pub struct Tree {
     state: State,
 }
@@ -276,15 +728,88 @@ impl Tree {
             data: tree,
             focus: initial_state.focus,
             is_host_focused,
+            root_to_screen_transform: Affine::IDENTITY,
+            reverse_relations: ChunkMap::new(),
+            role_index: ChunkMap::new(),
+            role_order: Arc::new(Mutex::new(HashMap::new())),
+            load_handler: None,
+            requested_loads: Arc::new(Mutex::new(HashSet::new())),
         };
         state.update(initial_state, is_host_focused, None);
         Self { state }
     }
 
+    /// Creates a tree containing only a root node with the given ID,
+    /// [`Role::Window`], and no children. The host isn't focused, so
+    /// there's no focused node until [`Tree::update_host_focus_state`]
+    /// is called. More nodes can be added with [`Tree::update`].
+    pub fn new_empty(root_id: NodeId) -> Self {
+        Self::new(
+            TreeUpdate {
+                nodes: vec![(root_id, NodeBuilder::new(Role::Window).build())],
+                tree: Some(TreeData::new(root_id)),
+                focus: root_id,
+            },
+            false,
+        )
+    }
+
+    /// Returns true if the root node has no children.
+    pub fn is_empty(&self) -> bool {
+        self.state.root().data().children().is_empty()
+    }
+
     pub fn update(&mut self, update: TreeUpdate) {
         self.state.update(update, self.state.is_host_focused, None);
     }
 
+    /// Detaches the subtree rooted at `old_root` from its parent, then
+    /// applies an update that can attach a new subtree in its place,
+    /// for example by including an updated version of the parent.
+    /// Returns the IDs of the nodes that are no longer in the tree,
+    /// in no particular order. These are collected while the update
+    /// is applied, without traversing the old subtree separately.
+    ///
+    /// If `old_root` is the tree's root, the update must replace the root
+    /// through [`TreeUpdate::tree`]. If `old_root` isn't in the tree,
+    /// an error is returned and the tree isn't changed.
+    pub fn replace_subtree(
+        &mut self,
+        old_root: NodeId,
+        mut update: TreeUpdate,
+    ) -> Result<Vec<NodeId>, ReplaceSubtreeError> {
+        let Some(old_root_state) = self.state.nodes.get(&old_root) else {
+            return Err(ReplaceSubtreeError::MissingRoot(old_root));
+        };
+        if let Some(ParentAndIndex(parent_id, _)) = old_root_state.parent_and_index {
+            let parent = self.state.nodes.get(&parent_id).unwrap();
+            let mut builder = NodeBuilder::from_node(&parent.data);
+            builder.set_children(
+                parent
+                    .data
+                    .children()
+                    .iter()
+                    .copied()
+                    .filter(|id| *id != old_root)
+                    .collect::<Vec<NodeId>>(),
+            );
+            update.nodes.insert(0, (parent_id, builder.build()));
+        }
+        let mut changes = InternalChanges::default();
+        self.state
+            .update(update, self.state.is_host_focused, Some(&mut changes));
+        Ok(changes.removed_node_ids.into_iter().collect())
+    }
+
+    /// Register a function to be called when [`Node::request_children`]
+    /// is first called on a node with [`Node::has_unloaded_children`] set.
+    /// The handler should ask the provider to send a [`TreeUpdate`] that
+    /// includes those children. Because it's called while the tree is
+    /// being read, it must not update the tree synchronously.
+    pub fn set_load_handler(&mut self, handler: impl 'static + Fn(NodeId) + Send + Sync) {
+        self.state.load_handler = Some(Arc::new(handler));
+    }
+
     pub fn update_and_process_changes(
         &mut self,
         update: TreeUpdate,
@@ -297,6 +822,14 @@ impl Tree {
         self.process_changes(old_state, changes, handler);
     }
 
+    /// Set the transform from the root node's coordinate space to screen
+    /// coordinates. Platform adapters typically derive this from the
+    /// window's position and scale factor, and update it whenever either
+    /// of those changes.
+    pub fn set_root_to_screen_transform(&mut self, transform: Affine) {
+        self.state.root_to_screen_transform = transform;
+    }
+
     pub fn update_host_focus_state(&mut self, is_host_focused: bool) {
         self.state.update_host_focus_state(is_host_focused, None);
     }
@@ -327,6 +860,9 @@ impl Tree {
             let old_node = old_state.node_by_id(*id).unwrap();
             let new_node = self.state.node_by_id(*id).unwrap();
             handler.node_updated(&old_node, &new_node);
+            if old_node.is_busy() != new_node.is_busy() {
+                handler.busy_state_changed(&new_node);
+            }
         }
         if old_state.focus_id() != self.state.focus_id() {
             let old_node = old_state.focus();
@@ -351,16 +887,38 @@ impl Tree {
                 }
             }
             handler.focus_moved(old_node.as_ref(), new_node.as_ref());
+        } else if let (Some(old_focus), Some(new_focus)) = (old_state.focus(), self.state.focus()) {
+            let old_active = old_focus.active_descendant();
+            let new_active = new_focus.active_descendant();
+            if old_active.map(|node| node.id()) != new_active.map(|node| node.id()) {
+                handler.active_descendant_changed(
+                    &new_focus,
+                    old_active.as_ref(),
+                    new_active.as_ref(),
+                );
+            }
         }
         for id in &changes.removed_node_ids {
             let node = old_state.node_by_id(*id).unwrap();
             handler.node_removed(&node);
         }
+        let new_title = self.state.title();
+        if old_state.title() != new_title {
+            handler.title_changed(new_title.as_deref());
+        }
     }
 
     pub fn state(&self) -> &State {
         &self.state
     }
+
+    /// Returns whether a node with the given ID is in the tree. This is
+    /// equivalent to [`State::has_node`]. The tree isn't internally
+    /// synchronized, so callers sharing it between threads must hold
+    /// their lock for as long as they rely on the result.
+    pub fn node_exists(&self, id: NodeId) -> bool {
+        self.state.has_node(id)
+    }
 }
 
 fn short_node_list<'a>(nodes: impl ExactSizeIterator<Item = &'a NodeId>) -> String {
@@ -386,36 +944,32 @@ fn short_node_list<'a>(nodes: impl ExactSizeIterator<Item = &'a NodeId>) -> Stri
 
 #[cfg(test)]
 mod tests {
-    use accesskit::{NodeBuilder, NodeId, Role, Tree, TreeUpdate};
+    use accesskit::{NodeBuilder, NodeId, Role, TreeUpdate, TreeUpdateBuilder};
+
+    use crate::tests::{node_with_children, Change, ChangeRecorder};
 
     #[test]
     fn init_tree_with_root_node() {
-        let update = TreeUpdate {
-            nodes: vec![(NodeId(0), NodeBuilder::new(Role::Window).build())],
-            tree: Some(Tree::new(NodeId(0))),
-            focus: NodeId(0),
-        };
-        let tree = super::Tree::new(update, false);
+        let tree = super::Tree::new_empty(NodeId(0));
         assert_eq!(NodeId(0), tree.state().root().id());
         assert_eq!(Role::Window, tree.state().root().role());
         assert!(tree.state().root().parent().is_none());
+        assert!(tree.state().focus().is_none());
+        assert!(tree.is_empty());
     }
 
     #[test]
     fn root_node_has_children() {
-        let update = TreeUpdate {
-            nodes: vec![
-                (NodeId(0), {
-                    let mut builder = NodeBuilder::new(Role::Window);
-                    builder.set_children(vec![NodeId(1), NodeId(2)]);
-                    builder.build()
-                }),
-                (NodeId(1), NodeBuilder::new(Role::Button).build()),
-                (NodeId(2), NodeBuilder::new(Role::Button).build()),
-            ],
-            tree: Some(Tree::new(NodeId(0))),
-            focus: NodeId(0),
-        };
+        let update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NodeId(1), NodeId(2)]);
+                builder.build()
+            })
+            .node(NodeId(1), NodeBuilder::new(Role::Button).build())
+            .node(NodeId(2), NodeBuilder::new(Role::Button).build())
+            .build();
         let tree = super::Tree::new(update, false);
         let state = tree.state();
         assert_eq!(
@@ -432,68 +986,23 @@ mod tests {
     #[test]
     fn add_child_to_root_node() {
         let root_builder = NodeBuilder::new(Role::Window);
-        let first_update = TreeUpdate {
-            nodes: vec![(NodeId(0), root_builder.clone().build())],
-            tree: Some(Tree::new(NodeId(0))),
-            focus: NodeId(0),
-        };
-        let mut tree = super::Tree::new(first_update, false);
+        let mut tree = super::Tree::new_empty(NodeId(0));
         assert_eq!(0, tree.state().root().children().count());
-        let second_update = TreeUpdate {
-            nodes: vec![
-                (NodeId(0), {
-                    let mut builder = root_builder;
-                    builder.push_child(NodeId(1));
-                    builder.build()
-                }),
-                (NodeId(1), NodeBuilder::new(Role::RootWebArea).build()),
-            ],
-            tree: None,
-            focus: NodeId(0),
-        };
-        struct Handler {
-            got_new_child_node: bool,
-            got_updated_root_node: bool,
-        }
-        fn unexpected_change() {
-            panic!("expected only new child node and updated root node");
-        }
-        impl super::ChangeHandler for Handler {
-            fn node_added(&mut self, node: &crate::Node) {
-                if node.id() == NodeId(1) {
-                    self.got_new_child_node = true;
-                    return;
-                }
-                unexpected_change();
-            }
-            fn node_updated(&mut self, old_node: &crate::Node, new_node: &crate::Node) {
-                if new_node.id() == NodeId(0)
-                    && old_node.data().children().is_empty()
-                    && new_node.data().children() == [NodeId(1)]
-                {
-                    self.got_updated_root_node = true;
-                    return;
-                }
-                unexpected_change();
-            }
-            fn focus_moved(
-                &mut self,
-                _old_node: Option<&crate::Node>,
-                _new_node: Option<&crate::Node>,
-            ) {
-                unexpected_change();
-            }
-            fn node_removed(&mut self, _node: &crate::Node) {
-                unexpected_change();
-            }
-        }
-        let mut handler = Handler {
-            got_new_child_node: false,
-            got_updated_root_node: false,
-        };
-        tree.update_and_process_changes(second_update, &mut handler);
-        assert!(handler.got_new_child_node);
-        assert!(handler.got_updated_root_node);
+        let second_update = TreeUpdateBuilder::new()
+            .node(NodeId(0), {
+                let mut builder = root_builder;
+                builder.push_child(NodeId(1));
+                builder.build()
+            })
+            .node(NodeId(1), NodeBuilder::new(Role::RootWebArea).build())
+            .focus(NodeId(0))
+            .build();
+        let mut recorder = ChangeRecorder::default();
+        tree.update_and_process_changes(second_update, &mut recorder);
+        assert_eq!(
+            vec![Change::Added(NodeId(1)), Change::Updated(NodeId(0))],
+            recorder.changes
+        );
         let state = tree.state();
         assert_eq!(1, state.root().children().count());
         assert_eq!(NodeId(1), state.root().children().next().unwrap().id());
@@ -501,156 +1010,63 @@ mod tests {
             NodeId(0),
             state.node_by_id(NodeId(1)).unwrap().parent().unwrap().id()
         );
+        assert!(!tree.is_empty());
     }
 
     #[test]
     fn remove_child_from_root_node() {
         let root_builder = NodeBuilder::new(Role::Window);
-        let first_update = TreeUpdate {
-            nodes: vec![
-                (NodeId(0), {
-                    let mut builder = root_builder.clone();
-                    builder.push_child(NodeId(1));
-                    builder.build()
-                }),
-                (NodeId(1), NodeBuilder::new(Role::RootWebArea).build()),
-            ],
-            tree: Some(Tree::new(NodeId(0))),
-            focus: NodeId(0),
-        };
+        let first_update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), {
+                let mut builder = root_builder.clone();
+                builder.push_child(NodeId(1));
+                builder.build()
+            })
+            .node(NodeId(1), NodeBuilder::new(Role::RootWebArea).build())
+            .build();
         let mut tree = super::Tree::new(first_update, false);
         assert_eq!(1, tree.state().root().children().count());
-        let second_update = TreeUpdate {
-            nodes: vec![(NodeId(0), root_builder.build())],
-            tree: None,
-            focus: NodeId(0),
-        };
-        struct Handler {
-            got_updated_root_node: bool,
-            got_removed_child_node: bool,
-        }
-        fn unexpected_change() {
-            panic!("expected only removed child node and updated root node");
-        }
-        impl super::ChangeHandler for Handler {
-            fn node_added(&mut self, _node: &crate::Node) {
-                unexpected_change();
-            }
-            fn node_updated(&mut self, old_node: &crate::Node, new_node: &crate::Node) {
-                if new_node.id() == NodeId(0)
-                    && old_node.data().children() == [NodeId(1)]
-                    && new_node.data().children().is_empty()
-                {
-                    self.got_updated_root_node = true;
-                    return;
-                }
-                unexpected_change();
-            }
-            fn focus_moved(
-                &mut self,
-                _old_node: Option<&crate::Node>,
-                _new_node: Option<&crate::Node>,
-            ) {
-                unexpected_change();
-            }
-            fn node_removed(&mut self, node: &crate::Node) {
-                if node.id() == NodeId(1) {
-                    self.got_removed_child_node = true;
-                    return;
-                }
-                unexpected_change();
-            }
-        }
-        let mut handler = Handler {
-            got_updated_root_node: false,
-            got_removed_child_node: false,
-        };
-        tree.update_and_process_changes(second_update, &mut handler);
-        assert!(handler.got_updated_root_node);
-        assert!(handler.got_removed_child_node);
+        let second_update = TreeUpdateBuilder::new()
+            .node(NodeId(0), root_builder.build())
+            .focus(NodeId(0))
+            .build();
+        let mut recorder = ChangeRecorder::default();
+        tree.update_and_process_changes(second_update, &mut recorder);
+        assert_eq!(
+            vec![Change::Updated(NodeId(0)), Change::Removed(NodeId(1))],
+            recorder.changes
+        );
         assert_eq!(0, tree.state().root().children().count());
         assert!(tree.state().node_by_id(NodeId(1)).is_none());
     }
 
     #[test]
     fn move_focus_between_siblings() {
-        let first_update = TreeUpdate {
-            nodes: vec![
-                (NodeId(0), {
-                    let mut builder = NodeBuilder::new(Role::Window);
-                    builder.set_children(vec![NodeId(1), NodeId(2)]);
-                    builder.build()
-                }),
-                (NodeId(1), NodeBuilder::new(Role::Button).build()),
-                (NodeId(2), NodeBuilder::new(Role::Button).build()),
-            ],
-            tree: Some(Tree::new(NodeId(0))),
-            focus: NodeId(1),
-        };
+        let first_update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NodeId(1), NodeId(2)]);
+                builder.build()
+            })
+            .node(NodeId(1), NodeBuilder::new(Role::Button).build())
+            .node(NodeId(2), NodeBuilder::new(Role::Button).build())
+            .focus(NodeId(1))
+            .build();
         let mut tree = super::Tree::new(first_update, true);
         assert!(tree.state().node_by_id(NodeId(1)).unwrap().is_focused());
-        let second_update = TreeUpdate {
-            nodes: vec![],
-            tree: None,
-            focus: NodeId(2),
-        };
-        struct Handler {
-            got_old_focus_node_update: bool,
-            got_new_focus_node_update: bool,
-            got_focus_change: bool,
-        }
-        fn unexpected_change() {
-            panic!("expected only focus change");
-        }
-        impl super::ChangeHandler for Handler {
-            fn node_added(&mut self, _node: &crate::Node) {
-                unexpected_change();
-            }
-            fn node_updated(&mut self, old_node: &crate::Node, new_node: &crate::Node) {
-                if old_node.id() == NodeId(1)
-                    && new_node.id() == NodeId(1)
-                    && old_node.is_focused()
-                    && !new_node.is_focused()
-                {
-                    self.got_old_focus_node_update = true;
-                    return;
-                }
-                if old_node.id() == NodeId(2)
-                    && new_node.id() == NodeId(2)
-                    && !old_node.is_focused()
-                    && new_node.is_focused()
-                {
-                    self.got_new_focus_node_update = true;
-                    return;
-                }
-                unexpected_change();
-            }
-            fn focus_moved(
-                &mut self,
-                old_node: Option<&crate::Node>,
-                new_node: Option<&crate::Node>,
-            ) {
-                if let (Some(old_node), Some(new_node)) = (old_node, new_node) {
-                    if old_node.id() == NodeId(1) && new_node.id() == NodeId(2) {
-                        self.got_focus_change = true;
-                        return;
-                    }
-                }
-                unexpected_change();
-            }
-            fn node_removed(&mut self, _node: &crate::Node) {
-                unexpected_change();
-            }
-        }
-        let mut handler = Handler {
-            got_old_focus_node_update: false,
-            got_new_focus_node_update: false,
-            got_focus_change: false,
-        };
-        tree.update_and_process_changes(second_update, &mut handler);
-        assert!(handler.got_old_focus_node_update);
-        assert!(handler.got_new_focus_node_update);
-        assert!(handler.got_focus_change);
+        let second_update = TreeUpdateBuilder::new().focus(NodeId(2)).build();
+        let mut recorder = ChangeRecorder::default();
+        tree.update_and_process_changes(second_update, &mut recorder);
+        assert_eq!(
+            vec![
+                Change::Updated(NodeId(1)),
+                Change::Updated(NodeId(2)),
+                Change::FocusMoved(Some(NodeId(1)), Some(NodeId(2))),
+            ],
+            recorder.changes
+        );
         assert!(tree.state().node_by_id(NodeId(2)).unwrap().is_focused());
         assert!(!tree.state().node_by_id(NodeId(1)).unwrap().is_focused());
     }
@@ -658,72 +1074,35 @@ mod tests {
     #[test]
     fn update_node() {
         let child_builder = NodeBuilder::new(Role::Button);
-        let first_update = TreeUpdate {
-            nodes: vec![
-                (NodeId(0), {
-                    let mut builder = NodeBuilder::new(Role::Window);
-                    builder.set_children(vec![NodeId(1)]);
-                    builder.build()
-                }),
-                (NodeId(1), {
-                    let mut builder = child_builder.clone();
-                    builder.set_name("foo");
-                    builder.build()
-                }),
-            ],
-            tree: Some(Tree::new(NodeId(0))),
-            focus: NodeId(0),
-        };
+        let first_update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NodeId(1)]);
+                builder.build()
+            })
+            .node(NodeId(1), {
+                let mut builder = child_builder.clone();
+                builder.set_name("foo");
+                builder.build()
+            })
+            .build();
         let mut tree = super::Tree::new(first_update, false);
         assert_eq!(
             Some("foo".into()),
             tree.state().node_by_id(NodeId(1)).unwrap().name()
         );
-        let second_update = TreeUpdate {
-            nodes: vec![(NodeId(1), {
+        let second_update = TreeUpdateBuilder::new()
+            .node(NodeId(1), {
                 let mut builder = child_builder;
                 builder.set_name("bar");
                 builder.build()
-            })],
-            tree: None,
-            focus: NodeId(0),
-        };
-        struct Handler {
-            got_updated_child_node: bool,
-        }
-        fn unexpected_change() {
-            panic!("expected only updated child node");
-        }
-        impl super::ChangeHandler for Handler {
-            fn node_added(&mut self, _node: &crate::Node) {
-                unexpected_change();
-            }
-            fn node_updated(&mut self, old_node: &crate::Node, new_node: &crate::Node) {
-                if new_node.id() == NodeId(1)
-                    && old_node.name() == Some("foo".into())
-                    && new_node.name() == Some("bar".into())
-                {
-                    self.got_updated_child_node = true;
-                    return;
-                }
-                unexpected_change();
-            }
-            fn focus_moved(
-                &mut self,
-                _old_node: Option<&crate::Node>,
-                _new_node: Option<&crate::Node>,
-            ) {
-                unexpected_change();
-            }
-            fn node_removed(&mut self, _node: &crate::Node) {
-                unexpected_change();
-            }
-        }
-        let mut handler = Handler {
-            got_updated_child_node: false,
-        };
-        tree.update_and_process_changes(second_update, &mut handler);
-        assert!(handler.got_updated_child_node);
+            })
+            .focus(NodeId(0))
+            .build();
+        let mut recorder = ChangeRecorder::default();
+        tree.update_and_process_changes(second_update, &mut recorder);
+        assert_eq!(vec![Change::Updated(NodeId(1))], recorder.changes);
         assert_eq!(
             Some("bar".into()),
             tree.state().node_by_id(NodeId(1)).unwrap().name()
@@ -736,46 +1115,754 @@ mod tests {
     // an immediate-mode GUI.
     #[test]
     fn no_change_update() {
-        let update = TreeUpdate {
-            nodes: vec![
-                (NodeId(0), {
-                    let mut builder = NodeBuilder::new(Role::Window);
-                    builder.set_children(vec![NodeId(1)]);
-                    builder.build()
-                }),
-                (NodeId(1), {
-                    let mut builder = NodeBuilder::new(Role::Button);
-                    builder.set_name("foo");
-                    builder.build()
-                }),
+        let update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NodeId(1)]);
+                builder.build()
+            })
+            .node(NodeId(1), {
+                let mut builder = NodeBuilder::new(Role::Button);
+                builder.set_name("foo");
+                builder.build()
+            })
+            .build();
+        let mut tree = super::Tree::new(update.clone(), false);
+        let mut recorder = ChangeRecorder::default();
+        tree.update_and_process_changes(update, &mut recorder);
+        assert_eq!(Vec::<Change>::new(), recorder.changes);
+    }
+
+    #[test]
+    fn load_unloaded_children() {
+        use std::sync::{Arc, Mutex};
+
+        let update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), {
+                let mut builder = NodeBuilder::new(Role::Document);
+                builder.set_children(vec![NodeId(1)]);
+                builder.build()
+            })
+            .node(NodeId(1), {
+                let mut builder = NodeBuilder::new(Role::Section);
+                builder.set_has_unloaded_children();
+                builder.build()
+            })
+            .build();
+        let mut tree = super::Tree::new(update, false);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        tree.set_load_handler({
+            let requests = Arc::clone(&requests);
+            move |id| requests.lock().unwrap().push(id)
+        });
+
+        let section = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert!(section.has_unloaded_children());
+        // Plain traversal doesn't request the children.
+        assert_eq!(0, section.children().count());
+        assert_eq!(
+            0,
+            section
+                .filtered_children(|_| crate::FilterResult::Include)
+                .count()
+        );
+        assert!(requests.lock().unwrap().is_empty());
+        section.request_children();
+        section.request_children();
+        assert_eq!(vec![NodeId(1)], *requests.lock().unwrap());
+
+        let second_update = TreeUpdateBuilder::new()
+            .node(NodeId(1), {
+                let mut builder = NodeBuilder::new(Role::Section);
+                builder.set_children(vec![NodeId(2), NodeId(3)]);
+                builder.build()
+            })
+            .node(NodeId(2), NodeBuilder::new(Role::Paragraph).build())
+            .node(NodeId(3), NodeBuilder::new(Role::Paragraph).build())
+            .focus(NodeId(0))
+            .build();
+        tree.update(second_update);
+        let section = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert!(!section.has_unloaded_children());
+        assert_eq!(2, section.children().count());
+        section.request_children();
+        assert_eq!(vec![NodeId(1)], *requests.lock().unwrap());
+    }
+
+    fn subtree_replacement_fixture() -> (TreeUpdate, TreeUpdate) {
+        let initial_update = TreeUpdateBuilder::new()
+            .tree_root(NodeId(0))
+            .node(NodeId(0), node_with_children(Role::Window, vec![NodeId(1)]))
+            .node(
+                NodeId(1),
+                node_with_children(Role::Document, vec![NodeId(2), NodeId(3)]),
+            )
+            .node(NodeId(2), NodeBuilder::new(Role::Paragraph).build())
+            .node(NodeId(3), node_with_children(Role::List, vec![NodeId(4)]))
+            .node(NodeId(4), NodeBuilder::new(Role::ListItem).build())
+            .build();
+        let replacement_update = TreeUpdateBuilder::new()
+            .node(NodeId(0), node_with_children(Role::Window, vec![NodeId(5)]))
+            .node(
+                NodeId(5),
+                node_with_children(Role::Document, vec![NodeId(6)]),
+            )
+            .node(NodeId(6), NodeBuilder::new(Role::Paragraph).build())
+            .focus(NodeId(0))
+            .build();
+        (initial_update, replacement_update)
+    }
+
+    #[test]
+    fn replace_subtree() {
+        let (initial_update, replacement_update) = subtree_replacement_fixture();
+        let mut tree = super::Tree::new(initial_update, true);
+        let mut removed = tree.replace_subtree(NodeId(1), replacement_update).unwrap();
+        removed.sort();
+        assert_eq!(vec![NodeId(1), NodeId(2), NodeId(3), NodeId(4)], removed);
+        let root = tree.state().root();
+        assert_eq!(vec![NodeId(5)], root.child_ids().collect::<Vec<NodeId>>());
+        assert!(tree.node_exists(NodeId(6)));
+        assert!(!tree.node_exists(NodeId(1)));
+    }
+
+    #[test]
+    fn replace_subtree_detaches_old_root() {
+        let (initial_update, _) = subtree_replacement_fixture();
+        let mut tree = super::Tree::new(initial_update, true);
+        let mut removed = tree
+            .replace_subtree(NodeId(3), TreeUpdateBuilder::new().focus(NodeId(0)).build())
+            .unwrap();
+        removed.sort();
+        assert_eq!(vec![NodeId(3), NodeId(4)], removed);
+        let document = tree.state().node_by_id(NodeId(1)).unwrap();
+        assert_eq!(
+            vec![NodeId(2)],
+            document.child_ids().collect::<Vec<NodeId>>()
+        );
+    }
+
+    #[test]
+    fn replace_subtree_matches_update() {
+        let (initial_update, replacement_update) = subtree_replacement_fixture();
+        let mut replaced_tree = super::Tree::new(initial_update.clone(), true);
+        let mut removed = replaced_tree
+            .replace_subtree(NodeId(1), replacement_update.clone())
+            .unwrap();
+        let mut updated_tree = super::Tree::new(initial_update, true);
+        let mut recorder = ChangeRecorder::default();
+        updated_tree.update_and_process_changes(replacement_update, &mut recorder);
+        let mut removed_ids = recorder
+            .changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Removed(id) => Some(*id),
+                _ => None,
+            })
+            .collect::<Vec<NodeId>>();
+        removed.sort();
+        removed_ids.sort();
+        assert_eq!(removed_ids, removed);
+        assert_eq!(
+            updated_tree.state().serialize(),
+            replaced_tree.state().serialize()
+        );
+    }
+
+    #[test]
+    fn replace_missing_subtree() {
+        let (initial_update, replacement_update) = subtree_replacement_fixture();
+        let mut tree = super::Tree::new(initial_update.clone(), true);
+        assert_eq!(
+            Err(super::ReplaceSubtreeError::MissingRoot(NodeId(10))),
+            tree.replace_subtree(NodeId(10), replacement_update)
+        );
+        assert_eq!(
+            super::Tree::new(initial_update, true).state().serialize(),
+            tree.state().serialize()
+        );
+    }
+
+    #[test]
+    fn busy_state_transitions() {
+        let list = |busy: bool, name: &str| {
+            let mut builder = NodeBuilder::new(Role::List);
+            builder.set_name(name);
+            if busy {
+                builder.set_busy();
+            }
+            builder.build()
+        };
+        let root = {
+            let mut builder = NodeBuilder::new(Role::Window);
+            builder.set_children(vec![NodeId(1)]);
+            builder.build()
+        };
+        let mut tree = super::Tree::new(
+            TreeUpdateBuilder::new()
+                .tree_root(NodeId(0))
+                .node(NodeId(0), root)
+                .node(NodeId(1), list(false, "Results"))
+                .build(),
+            true,
+        );
+        let mut recorder = ChangeRecorder::default();
+        let update = |busy, name| {
+            TreeUpdateBuilder::new()
+                .node(NodeId(1), list(busy, name))
+                .focus(NodeId(0))
+                .build()
+        };
+        tree.update_and_process_changes(update(true, "Results"), &mut recorder);
+        assert_eq!(
+            vec![
+                Change::Updated(NodeId(1)),
+                Change::BusyStateChanged(NodeId(1), true),
+            ],
+            recorder.take()
+        );
+        tree.update_and_process_changes(update(true, "More results"), &mut recorder);
+        assert_eq!(vec![Change::Updated(NodeId(1))], recorder.take());
+        tree.update_and_process_changes(update(false, "More results"), &mut recorder);
+        assert_eq!(
+            vec![
+                Change::Updated(NodeId(1)),
+                Change::BusyStateChanged(NodeId(1), false),
             ],
-            tree: Some(Tree::new(NodeId(0))),
-            focus: NodeId(0),
+            recorder.take()
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_events() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{
+            field::{Field, Visit},
+            span, Event, Metadata, Subscriber,
         };
-        let mut tree = super::Tree::new(update.clone(), false);
-        struct Handler;
-        fn unexpected_change() {
-            panic!("expected no changes");
+
+        #[derive(Default)]
+        struct Captured {
+            spans: Vec<&'static str>,
+            messages: Vec<String>,
+        }
+
+        struct MessageVisitor(Option<String>);
+
+        impl Visit for MessageVisitor {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = Some(format!("{value:?}"));
+                }
+            }
         }
-        impl super::ChangeHandler for Handler {
-            fn node_added(&mut self, _node: &crate::Node) {
-                unexpected_change();
+
+        struct CapturingSubscriber(Arc<Mutex<Captured>>);
+
+        impl Subscriber for CapturingSubscriber {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
             }
-            fn node_updated(&mut self, _old_node: &crate::Node, _new_node: &crate::Node) {
-                unexpected_change();
+            fn new_span(&self, attributes: &span::Attributes<'_>) -> span::Id {
+                let mut captured = self.0.lock().unwrap();
+                captured.spans.push(attributes.metadata().name());
+                span::Id::from_u64(captured.spans.len() as u64)
             }
-            fn focus_moved(
-                &mut self,
-                _old_node: Option<&crate::Node>,
-                _new_node: Option<&crate::Node>,
-            ) {
-                unexpected_change();
+            fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+            fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut visitor = MessageVisitor(None);
+                event.record(&mut visitor);
+                if let Some(message) = visitor.0 {
+                    self.0.lock().unwrap().messages.push(message);
+                }
             }
-            fn node_removed(&mut self, _node: &crate::Node) {
-                unexpected_change();
+            fn enter(&self, _span: &span::Id) {}
+            fn exit(&self, _span: &span::Id) {}
+        }
+
+        let captured = Arc::new(Mutex::new(Captured::default()));
+        let subscriber = CapturingSubscriber(Arc::clone(&captured));
+        tracing::subscriber::with_default(subscriber, || {
+            let container = |children: Vec<NodeId>| {
+                let mut builder = NodeBuilder::new(Role::GenericContainer);
+                builder.set_children(children);
+                builder.build()
+            };
+            let hidden = {
+                let mut builder = NodeBuilder::new(Role::Group);
+                builder.set_hidden();
+                builder.build()
+            };
+            let mut tree = super::Tree::new(
+                TreeUpdateBuilder::new()
+                    .tree_root(NodeId(0))
+                    .node(NodeId(0), container(vec![NodeId(1), NodeId(2), NodeId(3)]))
+                    .node(NodeId(1), container(vec![NodeId(4)]))
+                    .node(NodeId(2), container(vec![]))
+                    .node(NodeId(3), hidden)
+                    .node(NodeId(4), NodeBuilder::new(Role::Button).build())
+                    .build(),
+                false,
+            );
+            tree.update(
+                TreeUpdateBuilder::new()
+                    .node(NodeId(1), container(vec![]))
+                    .node(NodeId(2), container(vec![NodeId(4)]))
+                    .focus(NodeId(0))
+                    .build(),
+            );
+            tree.update(
+                TreeUpdateBuilder::new()
+                    .node(NodeId(2), container(vec![]))
+                    .focus(NodeId(0))
+                    .build(),
+            );
+            let _ = tree
+                .state()
+                .root()
+                .filtered_children(crate::common_filter)
+                .count();
+        });
+
+        let captured = captured.lock().unwrap();
+        assert_eq!(
+            vec!["tree_update", "tree_update", "tree_update"],
+            captured.spans
+        );
+        for expected in [
+            "node added",
+            "node reparented",
+            "node removed",
+            "excluding subtree from filtered traversal",
+        ] {
+            assert!(
+                captured.messages.iter().any(|message| message == expected),
+                "missing {expected:?} in {:?}",
+                captured.messages
+            );
+        }
+    }
+
+    #[test]
+    fn untouched_node_data_is_shared_across_updates() {
+        let root = {
+            let mut builder = NodeBuilder::new(Role::Window);
+            builder.set_children(vec![NodeId(1), NodeId(2)]);
+            builder.build()
+        };
+        let button = |name: &str| {
+            let mut builder = NodeBuilder::new(Role::Button);
+            builder.set_name(name);
+            builder.build()
+        };
+        let mut tree = super::Tree::new(
+            TreeUpdateBuilder::new()
+                .tree_root(NodeId(0))
+                .node(NodeId(0), root)
+                .node(NodeId(1), button("OK"))
+                .node(NodeId(2), button("Cancel"))
+                .build(),
+            false,
+        );
+        let old_state = tree.state().clone();
+        tree.update(
+            TreeUpdateBuilder::new()
+                .node(NodeId(1), button("Apply"))
+                .focus(NodeId(0))
+                .build(),
+        );
+        let new_state = tree.state();
+        let data = |state: &super::State, id| state.node_by_id(id).unwrap().data() as *const _;
+        assert_eq!(data(&old_state, NodeId(0)), data(new_state, NodeId(0)));
+        assert_eq!(data(&old_state, NodeId(2)), data(new_state, NodeId(2)));
+        assert_ne!(data(&old_state, NodeId(1)), data(new_state, NodeId(1)));
+    }
+
+    #[test]
+    fn list_box_active_descendant() {
+        const ROOT_ID: NodeId = NodeId(0);
+        const LIST_BOX_ID: NodeId = NodeId(1);
+        const OPTION_1_ID: NodeId = NodeId(2);
+        const OPTION_2_ID: NodeId = NodeId(3);
+        const HIDDEN_OPTION_ID: NodeId = NodeId(4);
+        const BUTTON_ID: NodeId = NodeId(5);
+
+        let list_box = |active_descendant: NodeId| {
+            let mut builder = NodeBuilder::new(Role::ListBox);
+            builder.set_children(vec![OPTION_1_ID, OPTION_2_ID, HIDDEN_OPTION_ID]);
+            builder.set_active_descendant(active_descendant);
+            builder.build()
+        };
+        let mut tree = super::Tree::new(
+            TreeUpdateBuilder::new()
+                .tree_root(ROOT_ID)
+                .node(ROOT_ID, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![LIST_BOX_ID, BUTTON_ID]);
+                    builder.build()
+                })
+                .node(LIST_BOX_ID, list_box(OPTION_1_ID))
+                .node(OPTION_1_ID, NodeBuilder::new(Role::ListBoxOption).build())
+                .node(OPTION_2_ID, NodeBuilder::new(Role::ListBoxOption).build())
+                .node(HIDDEN_OPTION_ID, {
+                    let mut builder = NodeBuilder::new(Role::ListBoxOption);
+                    builder.set_hidden();
+                    builder.build()
+                })
+                .node(BUTTON_ID, NodeBuilder::new(Role::Button).build())
+                .focus(LIST_BOX_ID)
+                .build(),
+            true,
+        );
+        let effective_focus = |tree: &super::Tree| {
+            tree.state()
+                .effective_focus(crate::common_filter)
+                .map(|node| node.id())
+        };
+        assert_eq!(Some(LIST_BOX_ID), tree.state().focus_id());
+        assert_eq!(Some(OPTION_1_ID), effective_focus(&tree));
+        let mut recorder = ChangeRecorder::default();
+
+        // Pressing the down arrow moves the active descendant,
+        // while input focus stays on the list box.
+        let update = |active_descendant, focus| {
+            TreeUpdateBuilder::new()
+                .node(LIST_BOX_ID, list_box(active_descendant))
+                .focus(focus)
+                .build()
+        };
+        tree.update_and_process_changes(update(OPTION_2_ID, LIST_BOX_ID), &mut recorder);
+        assert_eq!(Some(LIST_BOX_ID), tree.state().focus_id());
+        assert_eq!(Some(OPTION_2_ID), effective_focus(&tree));
+        // An active descendant that the filter excludes isn't described.
+        tree.update_and_process_changes(update(HIDDEN_OPTION_ID, LIST_BOX_ID), &mut recorder);
+        assert_eq!(Some(LIST_BOX_ID), effective_focus(&tree));
+        tree.update_and_process_changes(update(OPTION_1_ID, BUTTON_ID), &mut recorder);
+        assert_eq!(Some(BUTTON_ID), effective_focus(&tree));
+        assert_eq!(
+            vec![
+                Change::Updated(LIST_BOX_ID),
+                Change::ActiveDescendantChanged(LIST_BOX_ID, Some(OPTION_1_ID), Some(OPTION_2_ID)),
+                Change::Updated(LIST_BOX_ID),
+                Change::ActiveDescendantChanged(
+                    LIST_BOX_ID,
+                    Some(OPTION_2_ID),
+                    Some(HIDDEN_OPTION_ID)
+                ),
+                Change::Updated(LIST_BOX_ID),
+                Change::Updated(BUTTON_ID),
+                Change::FocusMoved(Some(LIST_BOX_ID), Some(BUTTON_ID)),
+            ],
+            recorder.changes
+        );
+    }
+
+    #[test]
+    fn effective_focus_stops_at_cycles() {
+        const ROOT_ID: NodeId = NodeId(0);
+        const GRID_ID: NodeId = NodeId(1);
+        const ROW_ID: NodeId = NodeId(2);
+
+        let node = |role, children: Vec<NodeId>, active_descendant| {
+            let mut builder = NodeBuilder::new(role);
+            builder.set_children(children);
+            builder.set_active_descendant(active_descendant);
+            builder.build()
+        };
+        let tree = super::Tree::new(
+            TreeUpdateBuilder::new()
+                .tree_root(ROOT_ID)
+                .node(ROOT_ID, node(Role::Window, vec![GRID_ID], GRID_ID))
+                .node(GRID_ID, node(Role::Grid, vec![ROW_ID], ROW_ID))
+                .node(ROW_ID, node(Role::Row, vec![], ROOT_ID))
+                .focus(ROOT_ID)
+                .build(),
+            true,
+        );
+        assert_eq!(
+            Some(ROW_ID),
+            tree.state()
+                .effective_focus(|_| crate::FilterResult::Include)
+                .map(|node| node.id())
+        );
+    }
+
+    #[test]
+    fn title_changes() {
+        const ROOT_ID: NodeId = NodeId(0);
+        const HEADING_ID: NodeId = NodeId(1);
+        const SUBHEADING_ID: NodeId = NodeId(2);
+
+        let root = |name: Option<&str>, labelled_by: Vec<NodeId>| {
+            let mut builder = NodeBuilder::new(Role::Window);
+            builder.set_children(vec![HEADING_ID, SUBHEADING_ID]);
+            if let Some(name) = name {
+                builder.set_name(name);
             }
+            builder.set_labelled_by(labelled_by);
+            builder.build()
+        };
+        let heading = |name: &str| {
+            let mut builder = NodeBuilder::new(Role::Heading);
+            builder.set_name(name);
+            builder.build()
+        };
+        let mut tree = super::Tree::new(
+            TreeUpdateBuilder::new()
+                .tree_root(ROOT_ID)
+                .node(ROOT_ID, root(Some("Inbox"), vec![]))
+                .node(HEADING_ID, heading("Drafts"))
+                .node(SUBHEADING_ID, heading("Sent"))
+                .build(),
+            true,
+        );
+        assert_eq!(Some("Inbox".into()), tree.state().title());
+        let mut recorder = ChangeRecorder::default();
+
+        let recorded_titles = |recorder: &mut ChangeRecorder| {
+            recorder
+                .take()
+                .into_iter()
+                .filter_map(|change| match change {
+                    Change::TitleChanged(title) => Some(title),
+                    _ => None,
+                })
+                .collect::<Vec<Option<String>>>()
+        };
+        let update = |nodes: Vec<(NodeId, accesskit::Node)>| {
+            TreeUpdateBuilder::new().nodes(nodes).focus(ROOT_ID).build()
+        };
+        tree.update_and_process_changes(
+            update(vec![(ROOT_ID, root(Some("Inbox (1)"), vec![]))]),
+            &mut recorder,
+        );
+        assert_eq!(
+            vec![Some("Inbox (1)".into())],
+            recorded_titles(&mut recorder)
+        );
+
+        tree.update_and_process_changes(
+            update(vec![(ROOT_ID, root(None, vec![HEADING_ID]))]),
+            &mut recorder,
+        );
+        assert_eq!(Some("Drafts".into()), tree.state().title());
+        tree.update_and_process_changes(
+            update(vec![(ROOT_ID, root(None, vec![SUBHEADING_ID]))]),
+            &mut recorder,
+        );
+        tree.update_and_process_changes(
+            update(vec![(SUBHEADING_ID, heading("Sent items"))]),
+            &mut recorder,
+        );
+        // Renaming a node that no longer labels the root has no effect.
+        tree.update_and_process_changes(
+            update(vec![(HEADING_ID, heading("Outbox"))]),
+            &mut recorder,
+        );
+        assert_eq!(
+            vec![
+                Some("Drafts".into()),
+                Some("Sent".into()),
+                Some("Sent items".into()),
+            ],
+            recorded_titles(&mut recorder)
+        );
+    }
+
+    #[test]
+    fn nodes_with_role_after_reparenting() {
+        let mut tree = super::Tree::new(
+            TreeUpdateBuilder::new()
+                .tree_root(NodeId(0))
+                .node(
+                    NodeId(0),
+                    node_with_children(Role::Document, vec![NodeId(1), NodeId(2)]),
+                )
+                .node(
+                    NodeId(1),
+                    node_with_children(Role::Section, vec![NodeId(3), NodeId(4)]),
+                )
+                .node(
+                    NodeId(2),
+                    node_with_children(Role::Section, vec![NodeId(5)]),
+                )
+                .node(NodeId(3), NodeBuilder::new(Role::Heading).build())
+                .node(NodeId(4), NodeBuilder::new(Role::Heading).build())
+                .node(NodeId(5), NodeBuilder::new(Role::Heading).build())
+                .build(),
+            false,
+        );
+        let heading_ids = |tree: &super::Tree| {
+            tree.state()
+                .nodes_with_role(Role::Heading)
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()
+        };
+        assert_eq!(vec![NodeId(3), NodeId(4), NodeId(5)], heading_ids(&tree));
+        assert_eq!(2, tree.state().nodes_with_role(Role::Section).len());
+
+        // Move the first heading from the first section to the end of
+        // the second one.
+        tree.update(
+            TreeUpdateBuilder::new()
+                .node(
+                    NodeId(1),
+                    node_with_children(Role::Section, vec![NodeId(4)]),
+                )
+                .node(
+                    NodeId(2),
+                    node_with_children(Role::Section, vec![NodeId(5), NodeId(3)]),
+                )
+                .focus(NodeId(0))
+                .build(),
+        );
+        assert_eq!(vec![NodeId(4), NodeId(5), NodeId(3)], heading_ids(&tree));
+
+        // Change a heading's role and remove another one.
+        tree.update(
+            TreeUpdateBuilder::new()
+                .node(NodeId(1), node_with_children(Role::Section, vec![]))
+                .node(NodeId(5), NodeBuilder::new(Role::Paragraph).build())
+                .focus(NodeId(0))
+                .build(),
+        );
+        assert_eq!(vec![NodeId(3)], heading_ids(&tree));
+        assert_eq!(
+            vec![NodeId(5)],
+            tree.state()
+                .nodes_with_role(Role::Paragraph)
+                .map(|node| node.id())
+                .collect::<Vec<NodeId>>()
+        );
+        assert_eq!(0, tree.state().nodes_with_role(Role::Table).len());
+    }
+
+    #[test]
+    fn move_node_out_of_removed_parent() {
+        let mut tree = super::Tree::new(
+            TreeUpdateBuilder::new()
+                .tree_root(NodeId(0))
+                .node(
+                    NodeId(0),
+                    node_with_children(Role::Window, vec![NodeId(1), NodeId(2)]),
+                )
+                .node(NodeId(1), node_with_children(Role::Group, vec![NodeId(3)]))
+                .node(NodeId(2), node_with_children(Role::Group, vec![]))
+                .node(NodeId(3), NodeBuilder::new(Role::Button).build())
+                .build(),
+            false,
+        );
+        // The new parent comes before the removal of the old one.
+        tree.update(
+            TreeUpdateBuilder::new()
+                .node(NodeId(2), node_with_children(Role::Group, vec![NodeId(3)]))
+                .node(NodeId(0), node_with_children(Role::Window, vec![NodeId(2)]))
+                .focus(NodeId(0))
+                .build(),
+        );
+        assert!(!tree.node_exists(NodeId(1)));
+        let button = tree.state().node_by_id(NodeId(3)).unwrap();
+        assert_eq!(Some(NodeId(2)), button.parent_id());
+    }
+
+    #[test]
+    fn diff_omits_unchanged_nodes() {
+        let tree = crate::tests::test_tree();
+        let mut new_full = tree.state().serialize();
+        assert_eq!(
+            TreeUpdateBuilder::new().focus(new_full.focus).build(),
+            tree.state().diff(&new_full)
+        );
+
+        let (id, data) = new_full
+            .nodes
+            .iter_mut()
+            .find(|(id, _)| *id == crate::tests::LABEL_2_0_ID)
+            .unwrap();
+        let mut builder = NodeBuilder::new(Role::Label);
+        builder.set_name("Renamed");
+        *data = builder.build();
+        let changed = (*id, data.clone());
+        new_full.tree.as_mut().unwrap().app_name = Some("App".into());
+        let diff = tree.state().diff(&new_full);
+        assert_eq!(vec![changed], diff.nodes);
+        assert_eq!(new_full.tree, diff.tree);
+    }
+
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self, bound: u64) -> u64 {
+            // xorshift64
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % bound
+        }
+    }
+
+    fn random_full_update(rng: &mut Rng) -> TreeUpdate {
+        const ID_POOL_SIZE: u64 = 24;
+        const ROLES: [Role; 3] = [Role::GenericContainer, Role::Button, Role::Label];
+        const NAMES: [Option<&str>; 3] = [None, Some("a"), Some("b")];
+
+        let mut ids = (1..ID_POOL_SIZE).map(NodeId).collect::<Vec<_>>();
+        for i in (1..ids.len()).rev() {
+            ids.swap(i, rng.next(i as u64 + 1) as usize);
+        }
+        let count = rng.next(ID_POOL_SIZE - 1) as usize;
+        ids.truncate(count);
+        ids.insert(0, NodeId(0));
+
+        let mut builders = ids
+            .iter()
+            .map(|_| {
+                let mut builder = NodeBuilder::new(ROLES[rng.next(3) as usize]);
+                if let Some(name) = NAMES[rng.next(3) as usize] {
+                    builder.set_name(name);
+                }
+                builder
+            })
+            .collect::<Vec<_>>();
+        for (i, id) in ids.iter().enumerate().skip(1) {
+            let parent = rng.next(i as u64) as usize;
+            builders[parent].push_child(*id);
+        }
+        let focus = ids[rng.next(ids.len() as u64) as usize];
+        let mut builder = TreeUpdateBuilder::new().tree_root(NodeId(0));
+        if rng.next(2) == 0 {
+            builder = builder.with_app_name("App");
+        }
+        for (id, node) in ids.into_iter().zip(builders) {
+            builder = builder.node(id, node.build());
+        }
+        builder.focus(focus).build()
+    }
+
+    #[test]
+    fn diff_matches_full_update() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..500 {
+            let old_full = random_full_update(&mut rng);
+            let new_full = random_full_update(&mut rng);
+            let mut fully_updated = super::Tree::new(old_full.clone(), false);
+            let mut diff_updated = super::Tree::new(old_full, false);
+            let diff = diff_updated.state().diff(&new_full);
+            assert!(diff.nodes.len() <= new_full.nodes.len());
+            fully_updated.update(new_full);
+            diff_updated.update(diff);
+            assert_eq!(
+                fully_updated.state().serialize(),
+                diff_updated.state().serialize()
+            );
+            assert_eq!(
+                fully_updated.state().nodes.len(),
+                diff_updated.state().nodes.len()
+            );
         }
-        let mut handler = Handler {};
-        tree.update_and_process_changes(update, &mut handler);
     }
 }
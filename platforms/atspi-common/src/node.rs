@@ -664,7 +664,7 @@ impl PlatformNode {
     {
         let context = self.upgrade_context()?;
         let tree = context.read_tree();
-        if tree.state().has_node(self.id) {
+        if tree.node_exists(self.id) {
             let request = f(tree.state(), &context);
             drop(tree);
             context.do_action(request);
@@ -3,10 +3,7 @@
 // the LICENSE-APACHE file) or the MIT license (found in
 // the LICENSE-MIT file), at your option.
 
-use accesskit::{
-    ActionHandler, ActionRequest, ActivationHandler, NodeBuilder, NodeId, Role, Tree as TreeData,
-    TreeUpdate,
-};
+use accesskit::{ActionHandler, ActionRequest, ActivationHandler, NodeId, TreeUpdate};
 use accesskit_consumer::{FilterResult, Tree};
 use objc2::rc::{Id, WeakId};
 use objc2_app_kit::NSView;
@@ -145,6 +142,21 @@ impl Adapter {
         }
     }
 
+    /// Returns the view focus state most recently passed to
+    /// [`Adapter::update_view_focus_state`], or to [`Adapter::new`]
+    /// if it hasn't been updated since.
+    pub fn is_view_focused(&self) -> bool {
+        match &self.state {
+            State::Inactive {
+                is_view_focused, ..
+            } => *is_view_focused,
+            State::Placeholder {
+                is_view_focused, ..
+            } => *is_view_focused,
+            State::Active(context) => context.tree.borrow().state().is_host_focused(),
+        }
+    }
+
     fn get_or_init_context<H: ActivationHandler + ?Sized>(
         &mut self,
         activation_handler: &mut H,
@@ -164,12 +176,7 @@ impl Adapter {
                     result
                 }
                 None => {
-                    let placeholder_update = TreeUpdate {
-                        nodes: vec![(PLACEHOLDER_ROOT_ID, NodeBuilder::new(Role::Window).build())],
-                        tree: Some(TreeData::new(PLACEHOLDER_ROOT_ID)),
-                        focus: PLACEHOLDER_ROOT_ID,
-                    };
-                    let placeholder_tree = Tree::new(placeholder_update, false);
+                    let placeholder_tree = Tree::new_empty(PLACEHOLDER_ROOT_ID);
                     let placeholder_context = Context::new(
                         view.clone(),
                         placeholder_tree,
@@ -244,6 +244,13 @@ impl SubclassingAdapter {
         let mut state = self.associated.ivars().state.borrow_mut();
         state.adapter.update_view_focus_state(is_focused)
     }
+
+    /// Returns the view focus state most recently passed to
+    /// [`SubclassingAdapter::update_view_focus_state`].
+    pub fn is_view_focused(&self) -> bool {
+        let state = self.associated.ivars().state.borrow();
+        state.adapter.is_view_focused()
+    }
 }
 
 impl Drop for SubclassingAdapter {
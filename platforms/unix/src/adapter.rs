@@ -179,6 +179,21 @@ impl Adapter {
             AdapterState::Active(r#impl) => r#impl.update_window_focus_state(is_focused),
         }
     }
+
+    /// Returns the window focus state most recently passed to
+    /// [`Adapter::update_window_focus_state`].
+    pub fn is_window_focused(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        match &*state {
+            AdapterState::Inactive {
+                is_window_focused, ..
+            } => *is_window_focused,
+            AdapterState::Pending {
+                is_window_focused, ..
+            } => *is_window_focused,
+            AdapterState::Active(r#impl) => r#impl.is_window_focused(),
+        }
+    }
 }
 
 impl Drop for Adapter {
@@ -2,7 +2,7 @@
 
 use accesskit::{
     Action, ActionHandler, ActionRequest, ActivationHandler, DefaultActionVerb, Live, Node,
-    NodeBuilder, NodeId, Rect, Role, Tree, TreeUpdate,
+    NodeBuilder, NodeId, Rect, Role, TreeUpdate, TreeUpdateBuilder,
 };
 use accesskit_windows::Adapter;
 use once_cell::sync::Lazy;
@@ -105,24 +105,17 @@ impl ActivationHandler for InnerWindowState {
         let root = self.build_root();
         let button_1 = build_button(BUTTON_1_ID, "Button 1");
         let button_2 = build_button(BUTTON_2_ID, "Button 2");
-        let mut tree = Tree::new(WINDOW_ID);
-        tree.app_name = Some("hello_world".to_string());
-
-        let mut result = TreeUpdate {
-            nodes: vec![
-                (WINDOW_ID, root),
-                (BUTTON_1_ID, button_1),
-                (BUTTON_2_ID, button_2),
-            ],
-            tree: Some(tree),
-            focus: self.focus,
-        };
+        let mut builder = TreeUpdateBuilder::new()
+            .tree_root(WINDOW_ID)
+            .with_app_name("hello_world")
+            .node(WINDOW_ID, root)
+            .node(BUTTON_1_ID, button_1)
+            .node(BUTTON_2_ID, button_2)
+            .focus(self.focus);
         if let Some(announcement) = &self.announcement {
-            result
-                .nodes
-                .push((ANNOUNCEMENT_ID, build_announcement(announcement)));
+            builder = builder.node(ANNOUNCEMENT_ID, build_announcement(announcement));
         }
-        Some(result)
+        Some(builder.build())
     }
 }
 
@@ -135,11 +128,9 @@ impl WindowState {
     fn set_focus(&self, focus: NodeId) {
         self.inner_state.borrow_mut().focus = focus;
         let mut adapter = self.adapter.borrow_mut();
-        if let Some(events) = adapter.update_if_active(|| TreeUpdate {
-            nodes: vec![],
-            tree: None,
-            focus,
-        }) {
+        if let Some(events) =
+            adapter.update_if_active(|| TreeUpdateBuilder::new().focus(focus).build())
+        {
             drop(adapter);
             events.raise();
         }
@@ -157,11 +148,11 @@ impl WindowState {
         if let Some(events) = adapter.update_if_active(|| {
             let announcement = build_announcement(text);
             let root = inner_state.build_root();
-            TreeUpdate {
-                nodes: vec![(ANNOUNCEMENT_ID, announcement), (WINDOW_ID, root)],
-                tree: None,
-                focus: inner_state.focus,
-            }
+            TreeUpdateBuilder::new()
+                .node(ANNOUNCEMENT_ID, announcement)
+                .node(WINDOW_ID, root)
+                .focus(inner_state.focus)
+                .build()
         }) {
             drop(adapter);
             drop(inner_state);
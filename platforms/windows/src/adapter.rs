@@ -3,9 +3,7 @@
 // the LICENSE-APACHE file) or the MIT license (found in
 // the LICENSE-MIT file), at your option.
 
-use accesskit::{
-    ActionHandler, ActivationHandler, Live, NodeBuilder, NodeId, Role, Tree as TreeData, TreeUpdate,
-};
+use accesskit::{ActionHandler, ActivationHandler, Live, NodeId, Role, TreeUpdate};
 use accesskit_consumer::{FilterResult, Node, Tree, TreeChangeHandler};
 use std::{
     collections::HashSet,
@@ -16,10 +14,15 @@ use windows::Win32::{
     UI::{Accessibility::*, WindowsAndMessaging::*},
 };
 
+#[cfg(feature = "msaa")]
+use windows::core::Interface;
+
+#[cfg(feature = "msaa")]
+use crate::msaa::IAccessibleBridge;
 use crate::{
     context::{ActionHandlerNoMut, ActionHandlerWrapper, Context},
     filters::filter,
-    node::{NodeWrapper, PlatformNode},
+    node::{runtime_id_from_node_id, NodeWrapper, PlatformNode},
     util::QueuedEvent,
 };
 
@@ -36,6 +39,9 @@ struct AdapterChangeHandler<'a> {
     context: &'a Arc<Context>,
     queue: Vec<QueuedEvent>,
     text_changed: HashSet<NodeId>,
+    // Pairs of (filtered parent ID, child ID).
+    added: Vec<(NodeId, NodeId)>,
+    removed: Vec<(NodeId, NodeId)>,
 }
 
 impl<'a> AdapterChangeHandler<'a> {
@@ -44,8 +50,29 @@ impl<'a> AdapterChangeHandler<'a> {
             context,
             queue: Vec::new(),
             text_changed: HashSet::new(),
+            added: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+/// Groups the children by parent, in the order in which each parent
+/// was first seen, omitting children whose parent is itself in the list,
+/// since the event for the parent covers the whole subtree.
+pub(crate) fn group_by_parent(pairs: &[(NodeId, NodeId)]) -> Vec<(NodeId, Vec<NodeId>)> {
+    let children: HashSet<NodeId> = pairs.iter().map(|(_, child)| *child).collect();
+    let mut groups: Vec<(NodeId, Vec<NodeId>)> = Vec::new();
+    for (parent, child) in pairs {
+        if children.contains(parent) {
+            continue;
+        }
+        if let Some((_, group)) = groups.iter_mut().find(|(id, _)| id == parent) {
+            group.push(*child);
+        } else {
+            groups.push((*parent, vec![*child]));
         }
     }
+    groups
 }
 
 impl AdapterChangeHandler<'_> {
@@ -80,6 +107,50 @@ impl AdapterChangeHandler<'_> {
             self.insert_text_change_if_needed_parent(node);
         }
     }
+
+    fn structure_changed_event(
+        &self,
+        element_id: NodeId,
+        change_type: StructureChangeType,
+        runtime_id_source: NodeId,
+    ) -> QueuedEvent {
+        let platform_node = PlatformNode::new(self.context, element_id);
+        let element: IRawElementProviderSimple = platform_node.into();
+        QueuedEvent::StructureChanged {
+            element,
+            change_type,
+            runtime_id: runtime_id_from_node_id(runtime_id_source).to_vec(),
+        }
+    }
+
+    /// Queues structure changed events for the nodes that were added
+    /// or removed. Sibling insertions or removals are reported as a single
+    /// bulk event on their parent, so clients don't have to process
+    /// each child separately.
+    fn enqueue_structure_changes(&mut self) {
+        for (parent, children) in group_by_parent(&self.added) {
+            let event = if let [child] = children[..] {
+                self.structure_changed_event(child, StructureChangeType_ChildAdded, child)
+            } else {
+                self.structure_changed_event(parent, StructureChangeType_ChildrenBulkAdded, parent)
+            };
+            self.queue.push(event);
+        }
+        for (parent, children) in group_by_parent(&self.removed) {
+            // For removals, the event is raised on the parent, since
+            // the child is no longer available.
+            let event = if let [child] = children[..] {
+                self.structure_changed_event(parent, StructureChangeType_ChildRemoved, child)
+            } else {
+                self.structure_changed_event(
+                    parent,
+                    StructureChangeType_ChildrenBulkRemoved,
+                    parent,
+                )
+            };
+            self.queue.push(event);
+        }
+    }
 }
 
 impl TreeChangeHandler for AdapterChangeHandler<'_> {
@@ -88,6 +159,9 @@ impl TreeChangeHandler for AdapterChangeHandler<'_> {
         if filter(node) != FilterResult::Include {
             return;
         }
+        if let Some(parent) = node.filtered_parent(&filter) {
+            self.added.push((parent.id(), node.id()));
+        }
         if node.name().is_some() && node.live() != Live::Off {
             let platform_node = PlatformNode::new(self.context, node.id());
             let element: IRawElementProviderSimple = platform_node.into();
@@ -131,6 +205,12 @@ impl TreeChangeHandler for AdapterChangeHandler<'_> {
 
     fn node_removed(&mut self, node: &Node) {
         self.insert_text_change_if_needed(node);
+        if filter(node) != FilterResult::Include {
+            return;
+        }
+        if let Some(parent) = node.filtered_parent(&filter) {
+            self.removed.push((parent.id(), node.id()));
+        }
     }
 
     // TODO: handle other events (#20)
@@ -228,6 +308,7 @@ impl Adapter {
                 let mut handler = AdapterChangeHandler::new(context);
                 let mut tree = context.tree.write().unwrap();
                 tree.update_and_process_changes(update_factory(), &mut handler);
+                handler.enqueue_structure_changes();
                 Some(QueuedEvents(handler.queue))
             }
         }
@@ -264,10 +345,31 @@ impl Adapter {
         }
     }
 
+    /// Returns the window focus state most recently passed to
+    /// [`Adapter::update_window_focus_state`], or to [`Adapter::new`]
+    /// if it hasn't been updated since.
+    pub fn is_window_focused(&self) -> bool {
+        match &self.state {
+            State::Inactive {
+                is_window_focused, ..
+            } => *is_window_focused,
+            State::Placeholder(context) | State::Active(context) => {
+                context.read_tree().state().is_host_focused()
+            }
+        }
+    }
+
     /// Handle the `WM_GETOBJECT` window message. The accessibility tree
     /// is lazily initialized if necessary using the provided
     /// [`ActivationHandler`] implementation.
     ///
+    /// With the `msaa` feature enabled, requests for `OBJID_CLIENT` are
+    /// answered with a native MSAA `IAccessible` implementation, while
+    /// UIA clients continue to get the UIA provider. Either way, this
+    /// method is the only entry point for both kinds of clients, so
+    /// MSAA support requires the window to handle `WM_GETOBJECT`,
+    /// either directly or through [`SubclassingAdapter`](crate::SubclassingAdapter).
+    ///
     /// This returns an `Option` so the caller can pass the message
     /// to `DefWindowProc` if AccessKit decides not to handle it.
     /// The optional value is an `Into<LRESULT>` rather than simply an `LRESULT`
@@ -307,12 +409,8 @@ impl Adapter {
                 }
                 None => {
                     let hwnd = *hwnd;
-                    let placeholder_update = TreeUpdate {
-                        nodes: vec![(PLACEHOLDER_ROOT_ID, NodeBuilder::new(Role::Window).build())],
-                        tree: Some(TreeData::new(PLACEHOLDER_ROOT_ID)),
-                        focus: PLACEHOLDER_ROOT_ID,
-                    };
-                    let placeholder_tree = Tree::new(placeholder_update, *is_window_focused);
+                    let mut placeholder_tree = Tree::new_empty(PLACEHOLDER_ROOT_ID);
+                    placeholder_tree.update_host_focus_state(*is_window_focused);
                     let context =
                         Context::new(hwnd, placeholder_tree, Arc::clone(action_handler), true);
                     let platform_node = PlatformNode::unspecified_root(&context);
@@ -326,12 +424,18 @@ impl Adapter {
                 (context.hwnd, PlatformNode::new(context, node_id))
             }
         };
+        #[cfg(feature = "msaa")]
+        let accessible = (objid == OBJID_CLIENT.0).then(|| {
+            IAccessibleBridge::new(platform_node.context.clone(), platform_node.node_id).into()
+        });
         let el: IRawElementProviderSimple = platform_node.into();
         Some(WmGetObjectResult {
             hwnd,
             wparam,
             lparam,
             el,
+            #[cfg(feature = "msaa")]
+            accessible,
         })
     }
 }
@@ -360,10 +464,16 @@ struct WmGetObjectResult {
     wparam: WPARAM,
     lparam: LPARAM,
     el: IRawElementProviderSimple,
+    #[cfg(feature = "msaa")]
+    accessible: Option<IAccessible>,
 }
 
 impl From<WmGetObjectResult> for LRESULT {
     fn from(this: WmGetObjectResult) -> Self {
+        #[cfg(feature = "msaa")]
+        if let Some(accessible) = &this.accessible {
+            return unsafe { LresultFromObject(&IAccessible::IID, this.wparam, accessible) };
+        }
         unsafe { UiaReturnRawElementProvider(this.hwnd, this.wparam, this.lparam, &this.el) }
     }
 }
@@ -405,6 +515,21 @@ impl QueuedEvents {
                     }
                     .unwrap();
                 }
+                QueuedEvent::StructureChanged {
+                    element,
+                    change_type,
+                    mut runtime_id,
+                } => {
+                    unsafe {
+                        UiaRaiseStructureChangedEvent(
+                            &element,
+                            change_type,
+                            runtime_id.as_mut_ptr(),
+                            runtime_id.len() as i32,
+                        )
+                    }
+                    .unwrap();
+                }
             }
         }
     }
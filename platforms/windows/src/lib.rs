@@ -5,6 +5,8 @@
 
 mod context;
 mod filters;
+#[cfg(feature = "msaa")]
+mod msaa;
 mod node;
 mod text;
 mod util;
@@ -0,0 +1,474 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+// A native implementation of the legacy MSAA `IAccessible` interface,
+// for assistive technologies that don't use UI Automation. Windows can
+// already expose a UIA provider to MSAA clients through its built-in proxy,
+// but some older ATs only work well with a native `IAccessible`.
+//
+// Every node is exposed as a full accessible object, so the only child ID
+// accepted by the methods that take one is `CHILDID_SELF`.
+
+#![allow(non_upper_case_globals)]
+
+use accesskit::{
+    Action, ActionData, ActionRequest, DefaultActionVerb, NodeId, Point, Role, Toggled,
+};
+use accesskit_consumer::{Node, TreeState};
+use std::sync::{atomic::Ordering, Weak};
+use windows::{
+    core::*,
+    Win32::{
+        Foundation::*,
+        System::{Com::*, Variant::*},
+        UI::{Accessibility::*, WindowsAndMessaging::*},
+    },
+};
+
+use crate::{
+    context::Context,
+    filters::{filter, filter_with_root_exception},
+    util::*,
+};
+
+fn msaa_role(node: &Node) -> u32 {
+    match node.role() {
+        Role::Button | Role::DefaultButton => ROLE_SYSTEM_PUSHBUTTON,
+        Role::CheckBox | Role::MenuItemCheckBox | Role::Switch => ROLE_SYSTEM_CHECKBUTTON,
+        Role::RadioButton | Role::MenuItemRadio => ROLE_SYSTEM_RADIOBUTTON,
+        Role::Link => ROLE_SYSTEM_LINK,
+        Role::Image | Role::Canvas | Role::SvgRoot => ROLE_SYSTEM_GRAPHIC,
+        Role::Label | Role::Paragraph | Role::InlineTextBox | Role::Heading => {
+            ROLE_SYSTEM_STATICTEXT
+        }
+        Role::List | Role::ListBox | Role::DescriptionList => ROLE_SYSTEM_LIST,
+        Role::ListItem | Role::ListBoxOption | Role::MenuListOption => ROLE_SYSTEM_LISTITEM,
+        Role::Tree | Role::TreeGrid => ROLE_SYSTEM_OUTLINE,
+        Role::TreeItem => ROLE_SYSTEM_OUTLINEITEM,
+        Role::Table | Role::Grid | Role::ListGrid => ROLE_SYSTEM_TABLE,
+        Role::Row => ROLE_SYSTEM_ROW,
+        Role::Cell => ROLE_SYSTEM_CELL,
+        Role::ColumnHeader => ROLE_SYSTEM_COLUMNHEADER,
+        Role::RowHeader => ROLE_SYSTEM_ROWHEADER,
+        Role::Menu | Role::MenuListPopup => ROLE_SYSTEM_MENUPOPUP,
+        Role::MenuBar => ROLE_SYSTEM_MENUBAR,
+        Role::MenuItem => ROLE_SYSTEM_MENUITEM,
+        Role::ComboBox | Role::EditableComboBox => ROLE_SYSTEM_COMBOBOX,
+        Role::TabList => ROLE_SYSTEM_PAGETABLIST,
+        Role::Tab => ROLE_SYSTEM_PAGETAB,
+        Role::TabPanel => ROLE_SYSTEM_PROPERTYPAGE,
+        Role::Slider => ROLE_SYSTEM_SLIDER,
+        Role::SpinButton => ROLE_SYSTEM_SPINBUTTON,
+        Role::ProgressIndicator | Role::Meter => ROLE_SYSTEM_PROGRESSBAR,
+        Role::ScrollBar => ROLE_SYSTEM_SCROLLBAR,
+        Role::Toolbar => ROLE_SYSTEM_TOOLBAR,
+        Role::Tooltip => ROLE_SYSTEM_TOOLTIP,
+        Role::Alert | Role::AlertDialog => ROLE_SYSTEM_ALERT,
+        Role::Dialog => ROLE_SYSTEM_DIALOG,
+        Role::Group | Role::RadioGroup => ROLE_SYSTEM_GROUPING,
+        Role::Document | Role::RootWebArea | Role::Article => ROLE_SYSTEM_DOCUMENT,
+        Role::Splitter => ROLE_SYSTEM_SEPARATOR,
+        Role::Status => ROLE_SYSTEM_STATUSBAR,
+        Role::TitleBar => ROLE_SYSTEM_TITLEBAR,
+        Role::Window => ROLE_SYSTEM_CLIENT,
+        _ if node.is_text_input() => ROLE_SYSTEM_TEXT,
+        _ => ROLE_SYSTEM_CLIENT,
+    }
+}
+
+fn msaa_state(node: &Node, state: &TreeState) -> u32 {
+    let mut result = 0;
+    if node.is_focusable() {
+        result |= STATE_SYSTEM_FOCUSABLE;
+    }
+    if state.focus_id() == Some(node.id()) {
+        result |= STATE_SYSTEM_FOCUSED;
+    }
+    if node.is_disabled() {
+        result |= STATE_SYSTEM_UNAVAILABLE;
+    }
+    if node.is_hidden() {
+        result |= STATE_SYSTEM_INVISIBLE;
+    }
+    if node.is_read_only() {
+        result |= STATE_SYSTEM_READONLY;
+    }
+    if node.is_busy() {
+        result |= STATE_SYSTEM_BUSY;
+    }
+    match node.toggled() {
+        Some(Toggled::True) => result |= STATE_SYSTEM_CHECKED,
+        Some(Toggled::Mixed) => result |= STATE_SYSTEM_MIXED,
+        _ => (),
+    }
+    if let Some(selected) = node.is_selected() {
+        result |= STATE_SYSTEM_SELECTABLE;
+        if selected {
+            result |= STATE_SYSTEM_SELECTED;
+        }
+    }
+    if node.role() == Role::Link {
+        result |= STATE_SYSTEM_LINKED;
+    }
+    result
+}
+
+fn default_action_name(verb: DefaultActionVerb) -> &'static str {
+    match verb {
+        DefaultActionVerb::Click => "Click",
+        DefaultActionVerb::Focus => "Focus",
+        DefaultActionVerb::Check => "Check",
+        DefaultActionVerb::Uncheck => "Uncheck",
+        DefaultActionVerb::ClickAncestor => "Click ancestor",
+        DefaultActionVerb::Jump => "Jump",
+        DefaultActionVerb::Open => "Open",
+        DefaultActionVerb::Press => "Press",
+        DefaultActionVerb::Select => "Select",
+        DefaultActionVerb::Unselect => "Unselect",
+    }
+}
+
+fn check_child_id(varchild: &VARIANT) -> Result<()> {
+    match i32::try_from(varchild) {
+        Ok(id) if id == CHILDID_SELF as i32 => Ok(()),
+        _ => Err(invalid_arg()),
+    }
+}
+
+fn member_not_found() -> Error {
+    DISP_E_MEMBERNOTFOUND.into()
+}
+
+fn child_id_self() -> VARIANT {
+    Variant::from(CHILDID_SELF as i32).into()
+}
+
+fn dispatch_variant(object: IDispatch) -> Result<VARIANT> {
+    // The VARIANT conversions in the `windows` crate only produce
+    // VT_UNKNOWN for interfaces, but MSAA clients expect VT_DISPATCH.
+    let unknown: VARIANT = Variant::from(IUnknown::from(object)).into();
+    let mut result = VARIANT::default();
+    unsafe { VariantChangeType(&mut result, &unknown, VAR_CHANGE_FLAGS(0), VT_DISPATCH) }?;
+    Ok(result)
+}
+
+#[implement(IAccessible)]
+pub(crate) struct IAccessibleBridge {
+    context: Weak<Context>,
+    node_id: Option<NodeId>,
+}
+
+impl IAccessibleBridge {
+    pub(crate) fn new(context: Weak<Context>, node_id: Option<NodeId>) -> Self {
+        Self { context, node_id }
+    }
+
+    fn resolve_with_context<F, T>(&self, f: F) -> Result<T>
+    where
+        for<'a> F: FnOnce(Node<'a>, &TreeState, &Context) -> Result<T>,
+    {
+        let context = upgrade(&self.context)?;
+        let tree = context.read_tree();
+        let state = tree.state();
+        let node = match self.node_id {
+            Some(id) => state.node_by_id(id).ok_or_else(element_not_available)?,
+            None => state.root(),
+        };
+        f(node, state, &context)
+    }
+
+    fn resolve<F, T>(&self, f: F) -> Result<T>
+    where
+        for<'a> F: FnOnce(Node<'a>) -> Result<T>,
+    {
+        self.resolve_with_context(|node, _, _| f(node))
+    }
+
+    fn do_action<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce() -> (Action, Option<ActionData>),
+    {
+        let context = upgrade(&self.context)?;
+        if context.is_placeholder.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let tree = context.read_tree();
+        let node_id = if let Some(id) = self.node_id {
+            if !tree.node_exists(id) {
+                return Err(element_not_available());
+            }
+            id
+        } else {
+            tree.state().root_id()
+        };
+        drop(tree);
+        let (action, data) = f();
+        context.do_action(ActionRequest {
+            target: node_id,
+            action,
+            data,
+        });
+        Ok(())
+    }
+
+    fn relative(&self, node_id: NodeId) -> IDispatch {
+        let accessible: IAccessible = Self::new(self.context.clone(), Some(node_id)).into();
+        accessible.into()
+    }
+
+    fn relative_variant(&self, self_node: &Node, node_id: NodeId) -> Result<VARIANT> {
+        if node_id == self_node.id() {
+            Ok(child_id_self())
+        } else {
+            dispatch_variant(self.relative(node_id))
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+impl IDispatch_Impl for IAccessibleBridge {
+    fn GetTypeInfoCount(&self) -> Result<u32> {
+        Ok(0)
+    }
+
+    fn GetTypeInfo(&self, _itinfo: u32, _lcid: u32) -> Result<ITypeInfo> {
+        Err(not_implemented())
+    }
+
+    fn GetIDsOfNames(
+        &self,
+        _riid: *const GUID,
+        _rgsznames: *const PCWSTR,
+        _cnames: u32,
+        _lcid: u32,
+        _rgdispid: *mut i32,
+    ) -> Result<()> {
+        Err(not_implemented())
+    }
+
+    fn Invoke(
+        &self,
+        _dispidmember: i32,
+        _riid: *const GUID,
+        _lcid: u32,
+        _wflags: DISPATCH_FLAGS,
+        _pdispparams: *const DISPPARAMS,
+        _pvarresult: *mut VARIANT,
+        _pexcepinfo: *mut EXCEPINFO,
+        _puargerr: *mut u32,
+    ) -> Result<()> {
+        Err(not_implemented())
+    }
+}
+
+#[allow(non_snake_case)]
+impl IAccessible_Impl for IAccessibleBridge {
+    fn accParent(&self) -> Result<IDispatch> {
+        self.resolve_with_context(|node, _, context| {
+            if let Some(parent) = node.filtered_parent(&filter_with_root_exception) {
+                return Ok(self.relative(parent.id()));
+            }
+            // The parent of the root is the window's standard accessible
+            // object, as it would be for any other window.
+            let mut result = std::ptr::null_mut();
+            unsafe {
+                AccessibleObjectFromWindow(
+                    context.hwnd,
+                    OBJID_WINDOW.0 as u32,
+                    &IDispatch::IID,
+                    &mut result,
+                )
+            }?;
+            Ok(unsafe { IDispatch::from_raw(result) })
+        })
+    }
+
+    fn accChildCount(&self) -> Result<i32> {
+        self.resolve(|node| Ok(node.filtered_children(&filter).count() as i32))
+    }
+
+    fn get_accChild(&self, varchild: &VARIANT) -> Result<IDispatch> {
+        let index = i32::try_from(varchild).map_err(|_| invalid_arg())?;
+        if index < 1 {
+            return Err(invalid_arg());
+        }
+        self.resolve(|node| {
+            node.filtered_children(&filter)
+                .nth((index - 1) as usize)
+                .map_or_else(|| Err(invalid_arg()), |child| Ok(self.relative(child.id())))
+        })
+    }
+
+    fn get_accName(&self, varchild: &VARIANT) -> Result<BSTR> {
+        check_child_id(varchild)?;
+        self.resolve_with_context(|node, _, context| {
+            if let Some(name) = node.name() {
+                Ok(name.into())
+            } else if node.is_root() {
+                Ok(window_title(context.hwnd).unwrap_or_default())
+            } else {
+                Ok(BSTR::new())
+            }
+        })
+    }
+
+    fn get_accValue(&self, varchild: &VARIANT) -> Result<BSTR> {
+        check_child_id(varchild)?;
+        self.resolve(|node| {
+            Ok(node
+                .value()
+                .or_else(|| node.value_text_or_formatted())
+                .map_or_else(BSTR::new, BSTR::from))
+        })
+    }
+
+    fn get_accDescription(&self, varchild: &VARIANT) -> Result<BSTR> {
+        check_child_id(varchild)?;
+        self.resolve(|node| Ok(node.description().map_or_else(BSTR::new, BSTR::from)))
+    }
+
+    fn get_accRole(&self, varchild: &VARIANT) -> Result<VARIANT> {
+        check_child_id(varchild)?;
+        self.resolve(|node| Ok(Variant::from(msaa_role(&node) as i32).into()))
+    }
+
+    fn get_accState(&self, varchild: &VARIANT) -> Result<VARIANT> {
+        check_child_id(varchild)?;
+        self.resolve_with_context(|node, state, _| {
+            Ok(Variant::from(msaa_state(&node, state) as i32).into())
+        })
+    }
+
+    fn get_accHelp(&self, _varchild: &VARIANT) -> Result<BSTR> {
+        Err(member_not_found())
+    }
+
+    fn get_accHelpTopic(&self, _pszhelpfile: *mut BSTR, _varchild: &VARIANT) -> Result<i32> {
+        Err(member_not_found())
+    }
+
+    fn get_accKeyboardShortcut(&self, _varchild: &VARIANT) -> Result<BSTR> {
+        Err(member_not_found())
+    }
+
+    fn accFocus(&self) -> Result<VARIANT> {
+        self.resolve_with_context(|node, state, _| {
+            let Some(focus) = state.focus() else {
+                return Ok(VARIANT::default());
+            };
+            // Only report focus within this object's subtree.
+            let mut ancestor = Some(focus);
+            while let Some(current) = ancestor {
+                if current.id() == node.id() {
+                    return self.relative_variant(&node, focus.id());
+                }
+                ancestor = current.parent();
+            }
+            Ok(VARIANT::default())
+        })
+    }
+
+    fn accSelection(&self) -> Result<VARIANT> {
+        Err(member_not_found())
+    }
+
+    fn get_accDefaultAction(&self, varchild: &VARIANT) -> Result<BSTR> {
+        check_child_id(varchild)?;
+        self.resolve(|node| {
+            Ok(node
+                .default_action_verb()
+                .map_or_else(BSTR::new, |verb| default_action_name(verb).into()))
+        })
+    }
+
+    fn accSelect(&self, flagsselect: i32, varchild: &VARIANT) -> Result<()> {
+        check_child_id(varchild)?;
+        if (flagsselect as u32) & SELFLAG_TAKEFOCUS != 0 {
+            self.do_action(|| (Action::Focus, None))
+        } else {
+            Err(not_implemented())
+        }
+    }
+
+    fn accLocation(
+        &self,
+        pxleft: *mut i32,
+        pytop: *mut i32,
+        pcxwidth: *mut i32,
+        pcyheight: *mut i32,
+        varchild: &VARIANT,
+    ) -> Result<()> {
+        check_child_id(varchild)?;
+        if pxleft.is_null() || pytop.is_null() || pcxwidth.is_null() || pcyheight.is_null() {
+            return Err(invalid_arg());
+        }
+        self.resolve_with_context(|node, _, context| {
+            let Some(rect) = node.bounding_box() else {
+                return Err(E_FAIL.into());
+            };
+            let client_top_left = context.client_top_left();
+            unsafe {
+                *pxleft = (rect.x0 + client_top_left.x) as i32;
+                *pytop = (rect.y0 + client_top_left.y) as i32;
+                *pcxwidth = rect.width() as i32;
+                *pcyheight = rect.height() as i32;
+            }
+            Ok(())
+        })
+    }
+
+    fn accNavigate(&self, navdir: i32, varstart: &VARIANT) -> Result<VARIANT> {
+        check_child_id(varstart)?;
+        self.resolve(|node| {
+            let result = match navdir as u32 {
+                NAVDIR_FIRSTCHILD => node.filtered_children(&filter).next(),
+                NAVDIR_LASTCHILD => node.filtered_children(&filter).next_back(),
+                NAVDIR_NEXT => node.following_filtered_siblings(&filter).next(),
+                NAVDIR_PREVIOUS => node.preceding_filtered_siblings(&filter).next(),
+                // Spatial navigation isn't supported.
+                _ => return Err(not_implemented()),
+            };
+            match result {
+                Some(result) => dispatch_variant(self.relative(result.id())),
+                None => Ok(VARIANT::default()),
+            }
+        })
+    }
+
+    fn accHitTest(&self, xleft: i32, ytop: i32) -> Result<VARIANT> {
+        self.resolve_with_context(|node, _, context| {
+            let client_top_left = context.client_top_left();
+            let point = Point::new(
+                f64::from(xleft) - client_top_left.x,
+                f64::from(ytop) - client_top_left.y,
+            );
+            let point = node.transform().inverse() * point;
+            match node.node_at_point(point, &filter) {
+                Some(result) => self.relative_variant(&node, result.id()),
+                None => Ok(VARIANT::default()),
+            }
+        })
+    }
+
+    fn accDoDefaultAction(&self, varchild: &VARIANT) -> Result<()> {
+        check_child_id(varchild)?;
+        self.do_action(|| (Action::Default, None))
+    }
+
+    fn put_accName(&self, _varchild: &VARIANT, _szname: &BSTR) -> Result<()> {
+        // MSAA no longer supports setting the name.
+        Err(not_implemented())
+    }
+
+    fn put_accValue(&self, varchild: &VARIANT, szvalue: &BSTR) -> Result<()> {
+        check_child_id(varchild)?;
+        let has_value = self.resolve(|node| Ok(node.has_value()))?;
+        if !has_value {
+            return Err(not_implemented());
+        }
+        let value = szvalue.to_string();
+        self.do_action(|| (Action::SetValue, Some(ActionData::Value(value.into()))))
+    }
+}
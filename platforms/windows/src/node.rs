@@ -31,7 +31,7 @@ use crate::{
 
 const RUNTIME_ID_SIZE: usize = 3;
 
-fn runtime_id_from_node_id(id: NodeId) -> [i32; RUNTIME_ID_SIZE] {
+pub(crate) fn runtime_id_from_node_id(id: NodeId) -> [i32; RUNTIME_ID_SIZE] {
     static_assertions::assert_eq_size!(NodeIdContent, u64);
     let id = id.0;
     [
@@ -41,6 +41,98 @@ fn runtime_id_from_node_id(id: NodeId) -> [i32; RUNTIME_ID_SIZE] {
     ]
 }
 
+struct ScrollRange {
+    offset: f64,
+    min: f64,
+    max: f64,
+    page_size: f64,
+}
+
+impl ScrollRange {
+    fn new(offset: Option<f64>, min: Option<f64>, max: Option<f64>, page_size: f64) -> Self {
+        let min = min.unwrap_or(0.0);
+        Self {
+            offset: offset.unwrap_or(min),
+            min,
+            max: max.unwrap_or(min).max(min),
+            page_size,
+        }
+    }
+
+    fn horizontal(node: &Node) -> Self {
+        Self::new(
+            node.scroll_x(),
+            node.scroll_x_min(),
+            node.scroll_x_max(),
+            node.raw_bounds().map_or(0.0, |bounds| bounds.width()),
+        )
+    }
+
+    fn vertical(node: &Node) -> Self {
+        Self::new(
+            node.scroll_y(),
+            node.scroll_y_min(),
+            node.scroll_y_max(),
+            node.raw_bounds().map_or(0.0, |bounds| bounds.height()),
+        )
+    }
+
+    fn is_scrollable(&self) -> bool {
+        self.max > self.min
+    }
+
+    fn percent(&self) -> f64 {
+        if !self.is_scrollable() {
+            return UIA_ScrollPatternNoScroll;
+        }
+        ((self.offset - self.min) / (self.max - self.min) * 100.0).clamp(0.0, 100.0)
+    }
+
+    fn view_size(&self) -> f64 {
+        if !self.is_scrollable() || self.page_size <= 0.0 {
+            return 100.0;
+        }
+        self.page_size / (self.page_size + self.max - self.min) * 100.0
+    }
+
+    fn scrolled(&self, amount: ScrollAmount) -> Result<f64> {
+        if amount == ScrollAmount_NoAmount {
+            return Ok(self.offset);
+        }
+        if !self.is_scrollable() {
+            return Err(invalid_operation());
+        }
+        // A large change scrolls by a page, or by a tenth of the range
+        // if we don't know the page size. A small change is a tenth of that.
+        let large_change = if self.page_size > 0.0 {
+            self.page_size
+        } else {
+            (self.max - self.min) / 10.0
+        };
+        let delta = match amount {
+            ScrollAmount_LargeDecrement => -large_change,
+            ScrollAmount_SmallDecrement => -large_change / 10.0,
+            ScrollAmount_LargeIncrement => large_change,
+            ScrollAmount_SmallIncrement => large_change / 10.0,
+            _ => return Err(invalid_arg()),
+        };
+        Ok((self.offset + delta).clamp(self.min, self.max))
+    }
+
+    fn at_percent(&self, percent: f64) -> Result<f64> {
+        if percent == UIA_ScrollPatternNoScroll {
+            return Ok(self.offset);
+        }
+        if !self.is_scrollable() {
+            return Err(invalid_operation());
+        }
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(invalid_arg());
+        }
+        Ok(self.min + percent / 100.0 * (self.max - self.min))
+    }
+}
+
 pub(crate) struct NodeWrapper<'a>(pub(crate) &'a Node<'a>);
 
 impl<'a> NodeWrapper<'a> {
@@ -282,6 +374,10 @@ impl<'a> NodeWrapper<'a> {
         self.0.is_focusable()
     }
 
+    fn is_password(&self) -> bool {
+        self.0.is_protected()
+    }
+
     fn is_focused(&self) -> bool {
         self.0.is_focused()
     }
@@ -295,6 +391,10 @@ impl<'a> NodeWrapper<'a> {
         }
     }
 
+    fn is_data_valid_for_form(&self) -> bool {
+        self.0.invalid().is_none()
+    }
+
     fn automation_id(&self) -> Option<&str> {
         self.0.author_id()
     }
@@ -323,8 +423,28 @@ impl<'a> NodeWrapper<'a> {
         }
     }
 
+    fn is_expand_collapse_pattern_supported(&self) -> bool {
+        self.0.supports_expand_collapse()
+    }
+
+    fn expand_collapse_state(&self) -> ExpandCollapseState {
+        if self.0.is_expanded() == Some(true) {
+            ExpandCollapseState_Expanded
+        } else {
+            ExpandCollapseState_Collapsed
+        }
+    }
+
     fn is_invoke_pattern_supported(&self) -> bool {
+        // UIA clients only activate controls through patterns, so a provider
+        // that handles the default action without setting a verb must still
+        // expose Invoke, unless the action is covered by another pattern.
         self.0.is_invocable()
+            || (self.0.supports_action(Action::Default)
+                && self.0.default_action_verb().is_none()
+                && !self.is_toggle_pattern_supported()
+                && !self.is_expand_collapse_pattern_supported()
+                && !self.is_selection_item_pattern_supported())
     }
 
     fn is_value_pattern_supported(&self) -> bool {
@@ -398,10 +518,94 @@ impl<'a> NodeWrapper<'a> {
         }
     }
 
+    fn is_scroll_pattern_supported(&self) -> bool {
+        self.0.is_scrollable()
+    }
+
+    fn horizontal_scroll_percent(&self) -> f64 {
+        ScrollRange::horizontal(self.0).percent()
+    }
+
+    fn vertical_scroll_percent(&self) -> f64 {
+        ScrollRange::vertical(self.0).percent()
+    }
+
+    fn horizontal_view_size(&self) -> f64 {
+        ScrollRange::horizontal(self.0).view_size()
+    }
+
+    fn vertical_view_size(&self) -> f64 {
+        ScrollRange::vertical(self.0).view_size()
+    }
+
+    fn is_horizontally_scrollable(&self) -> bool {
+        self.0.is_horizontally_scrollable()
+    }
+
+    fn is_vertically_scrollable(&self) -> bool {
+        self.0.is_vertically_scrollable()
+    }
+
+    fn is_selection_pattern_supported(&self) -> bool {
+        self.0.is_container_with_selectable_children()
+    }
+
+    fn is_multiselectable(&self) -> bool {
+        self.0.is_multiselectable()
+    }
+
+    fn is_selection_required(&self) -> bool {
+        self.0.is_required()
+    }
+
     fn is_text_pattern_supported(&self) -> bool {
         self.0.supports_text_ranges()
     }
 
+    fn is_grid_pattern_supported(&self) -> bool {
+        self.0.is_table()
+    }
+
+    fn row_count(&self) -> i32 {
+        self.0.table_row_count().unwrap_or(0) as i32
+    }
+
+    fn column_count(&self) -> i32 {
+        self.0.table_column_count().unwrap_or(0) as i32
+    }
+
+    fn is_grid_item_pattern_supported(&self) -> bool {
+        self.0.is_table_cell() && self.0.containing_table().is_some()
+    }
+
+    fn row_index(&self) -> i32 {
+        self.0.table_row_index().unwrap_or(0) as i32
+    }
+
+    fn column_index(&self) -> i32 {
+        self.0.table_column_index().unwrap_or(0) as i32
+    }
+
+    fn row_span(&self) -> i32 {
+        self.0.table_row_span() as i32
+    }
+
+    fn column_span(&self) -> i32 {
+        self.0.table_column_span() as i32
+    }
+
+    fn is_table_pattern_supported(&self) -> bool {
+        self.is_grid_pattern_supported()
+    }
+
+    fn row_or_column_major(&self) -> RowOrColumnMajor {
+        RowOrColumnMajor_RowMajor
+    }
+
+    fn is_table_item_pattern_supported(&self) -> bool {
+        self.is_grid_item_pattern_supported()
+    }
+
     pub(crate) fn enqueue_property_changes(
         &self,
         queue: &mut Vec<QueuedEvent>,
@@ -460,14 +664,23 @@ impl<'a> NodeWrapper<'a> {
 
 #[implement(
     IRawElementProviderSimple,
+    IRawElementProviderSimple2,
     IRawElementProviderFragment,
     IRawElementProviderFragmentRoot,
     IToggleProvider,
     IInvokeProvider,
+    IExpandCollapseProvider,
     IValueProvider,
     IRangeValueProvider,
     ISelectionItemProvider,
-    ITextProvider
+    ISelectionProvider,
+    IScrollProvider,
+    ITextProvider,
+    ITextProvider2,
+    IGridProvider,
+    IGridItemProvider,
+    ITableProvider,
+    ITableItemProvider
 )]
 pub(crate) struct PlatformNode {
     pub(crate) context: Weak<Context>,
@@ -579,7 +792,7 @@ impl PlatformNode {
         }
         let tree = context.read_tree();
         let node_id = if let Some(id) = self.node_id {
-            if !tree.state().has_node(id) {
+            if !tree.node_exists(id) {
                 return Err(element_not_available());
             }
             id
@@ -611,6 +824,42 @@ impl PlatformNode {
     fn is_root(&self, state: &TreeState) -> bool {
         self.node_id.map_or(false, |id| id == state.root_id())
     }
+
+    fn set_scroll_offset<F>(&self, f: F) -> Result<()>
+    where
+        for<'a> F: FnOnce(Node<'a>) -> Result<Point>,
+    {
+        let offset = self.resolve(f)?;
+        self.do_action(|| {
+            (
+                Action::SetScrollOffset,
+                Some(ActionData::SetScrollOffset(offset)),
+            )
+        })
+    }
+
+    fn set_selected(&self, selected: bool) -> Result<()> {
+        let is_selected = self.resolve(|node| Ok(NodeWrapper(&node).is_selected()))?;
+        if is_selected == selected {
+            return Ok(());
+        }
+        self.do_default_action()
+    }
+
+    fn relative_array<F>(&self, f: F) -> Result<*mut SAFEARRAY>
+    where
+        for<'a> F: FnOnce(Node<'a>) -> Vec<NodeId>,
+    {
+        let ids = self.resolve(|node| Ok(f(node)))?;
+        let elements = ids
+            .into_iter()
+            .map(|id| {
+                let element: IRawElementProviderSimple = self.relative(id).into();
+                element.cast::<IUnknown>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(safe_array_from_com_slice(&elements))
+    }
 }
 
 #[allow(non_snake_case)]
@@ -620,6 +869,19 @@ impl IRawElementProviderSimple_Impl for PlatformNode {
     }
 
     fn GetPatternProvider(&self, pattern_id: UIA_PATTERN_ID) -> Result<IUnknown> {
+        // The name of this pattern doesn't follow the convention that
+        // the `patterns!` macro relies on, so we handle it here.
+        if pattern_id == UIA_TextPattern2Id {
+            return self.resolve(|node| {
+                if NodeWrapper(&node).is_text_pattern_supported() {
+                    // SAFETY: We know we're running inside a full COM implementation.
+                    let intermediate: ITextProvider2 = unsafe { self.cast() }?;
+                    intermediate.cast()
+                } else {
+                    Err(Error::empty())
+                }
+            });
+        }
         self.pattern_provider(pattern_id)
     }
 
@@ -662,6 +924,13 @@ impl IRawElementProviderSimple_Impl for PlatformNode {
     }
 }
 
+#[allow(non_snake_case)]
+impl IRawElementProviderSimple2_Impl for PlatformNode {
+    fn ShowContextMenu(&self) -> Result<()> {
+        self.do_action(|| (Action::ShowContextMenu, None))
+    }
+}
+
 #[allow(non_snake_case)]
 impl IRawElementProviderFragment_Impl for PlatformNode {
     fn Navigate(&self, direction: NavigateDirection) -> Result<IRawElementProviderFragment> {
@@ -874,8 +1143,10 @@ properties! {
     (IsControlElement, is_content_element),
     (IsEnabled, is_enabled),
     (IsKeyboardFocusable, is_focusable),
+    (IsPassword, is_password),
     (HasKeyboardFocus, is_focused),
     (LiveSetting, live_setting),
+    (IsDataValidForForm, is_data_valid_for_form),
     (AutomationId, automation_id),
     (ClassName, class_name),
     (Orientation, orientation)
@@ -894,6 +1165,17 @@ patterns! {
             self.do_default_action()
         }
     )),
+    (ExpandCollapse, is_expand_collapse_pattern_supported, (
+        (ExpandCollapseState, expand_collapse_state, ExpandCollapseState)
+    ), (
+        fn Expand(&self) -> Result<()> {
+            self.do_action(|| (Action::Expand, None))
+        },
+
+        fn Collapse(&self) -> Result<()> {
+            self.do_action(|| (Action::Collapse, None))
+        }
+    )),
     (Value, is_value_pattern_supported, (
         (Value, value, BSTR),
         (IsReadOnly, is_read_only, BOOL)
@@ -927,20 +1209,64 @@ patterns! {
         },
 
         fn AddToSelection(&self) -> Result<()> {
-            // TODO: implement when we work on list boxes (#23)
-            Err(not_implemented())
+            self.set_selected(true)
         },
 
         fn RemoveFromSelection(&self) -> Result<()> {
-            // TODO: implement when we work on list boxes (#23)
-            Err(not_implemented())
+            self.set_selected(false)
         },
 
         fn SelectionContainer(&self) -> Result<IRawElementProviderSimple> {
-            // TODO: implement when we work on list boxes (#23)
-            // We return E_FAIL here because that's what Chromium does
-            // if it can't find a container.
-            Err(E_FAIL.into())
+            self.resolve(|node| {
+                if let Some(container) = node.selection_container(&filter) {
+                    Ok(self.relative(container.id()).into())
+                } else {
+                    // We return E_FAIL here because that's what Chromium does
+                    // if it can't find a container.
+                    Err(E_FAIL.into())
+                }
+            })
+        }
+    )),
+    (Selection, is_selection_pattern_supported, (
+        (CanSelectMultiple, is_multiselectable, BOOL),
+        (IsSelectionRequired, is_selection_required, BOOL)
+    ), (
+        fn GetSelection(&self) -> Result<*mut SAFEARRAY> {
+            self.relative_array(|node| {
+                node.selection_container_descendants(filter)
+                    .into_iter()
+                    .filter(|descendant| {
+                        let wrapper = NodeWrapper(descendant);
+                        wrapper.is_selection_item_pattern_supported() && wrapper.is_selected()
+                    })
+                    .map(|descendant| descendant.id())
+                    .collect()
+            })
+        }
+    )),
+    (Scroll, is_scroll_pattern_supported, (
+        (HorizontalScrollPercent, horizontal_scroll_percent, f64),
+        (VerticalScrollPercent, vertical_scroll_percent, f64),
+        (HorizontalViewSize, horizontal_view_size, f64),
+        (VerticalViewSize, vertical_view_size, f64),
+        (HorizontallyScrollable, is_horizontally_scrollable, BOOL),
+        (VerticallyScrollable, is_vertically_scrollable, BOOL)
+    ), (
+        fn Scroll(&self, horizontal_amount: ScrollAmount, vertical_amount: ScrollAmount) -> Result<()> {
+            self.set_scroll_offset(|node| {
+                let x = ScrollRange::horizontal(&node).scrolled(horizontal_amount)?;
+                let y = ScrollRange::vertical(&node).scrolled(vertical_amount)?;
+                Ok(Point::new(x, y))
+            })
+        },
+
+        fn SetScrollPercent(&self, horizontal_percent: f64, vertical_percent: f64) -> Result<()> {
+            self.set_scroll_offset(|node| {
+                let x = ScrollRange::horizontal(&node).at_percent(horizontal_percent)?;
+                let y = ScrollRange::vertical(&node).at_percent(vertical_percent)?;
+                Ok(Point::new(x, y))
+            })
         }
     )),
     (Text, is_text_pattern_supported, (), (
@@ -995,9 +1321,86 @@ patterns! {
                 }
             })
         }
+    )),
+    (Grid, is_grid_pattern_supported, (
+        (RowCount, row_count, i32),
+        (ColumnCount, column_count, i32)
+    ), (
+        fn GetItem(&self, row: i32, column: i32) -> Result<IRawElementProviderSimple> {
+            let row = usize::try_from(row).map_err(|_| invalid_arg())?;
+            let column = usize::try_from(column).map_err(|_| invalid_arg())?;
+            self.resolve(|node| {
+                if let Some(cell) = node.table_cell_at(row, column) {
+                    Ok(self.relative(cell.id()).into())
+                } else {
+                    Err(invalid_arg())
+                }
+            })
+        }
+    )),
+    (GridItem, is_grid_item_pattern_supported, (
+        (Row, row_index, i32),
+        (Column, column_index, i32),
+        (RowSpan, row_span, i32),
+        (ColumnSpan, column_span, i32)
+    ), (
+        fn ContainingGrid(&self) -> Result<IRawElementProviderSimple> {
+            self.resolve(|node| {
+                if let Some(table) = node.containing_table() {
+                    Ok(self.relative(table.id()).into())
+                } else {
+                    Err(element_not_available())
+                }
+            })
+        }
+    )),
+    (Table, is_table_pattern_supported, (
+        (RowOrColumnMajor, row_or_column_major, RowOrColumnMajor)
+    ), (
+        fn GetRowHeaders(&self) -> Result<*mut SAFEARRAY> {
+            self.relative_array(|node| node.table_row_headers().map(|header| header.id()).collect())
+        },
+
+        fn GetColumnHeaders(&self) -> Result<*mut SAFEARRAY> {
+            self.relative_array(|node| node.table_column_headers().map(|header| header.id()).collect())
+        }
+    )),
+    (TableItem, is_table_item_pattern_supported, (), (
+        fn GetRowHeaderItems(&self) -> Result<*mut SAFEARRAY> {
+            self.relative_array(|node| node.table_cell_row_headers().map(|header| header.id()).collect())
+        },
+
+        fn GetColumnHeaderItems(&self) -> Result<*mut SAFEARRAY> {
+            self.relative_array(|node| node.table_cell_column_headers().map(|header| header.id()).collect())
+        }
     ))
 }
 
+#[allow(non_snake_case)]
+impl ITextProvider2_Impl for PlatformNode {
+    fn RangeFromAnnotation(
+        &self,
+        _annotation_element: Option<&IRawElementProviderSimple>,
+    ) -> Result<ITextRangeProvider> {
+        // We don't support annotations.
+        Err(not_implemented())
+    }
+
+    fn GetCaretRange(&self, is_active: *mut BOOL) -> Result<ITextRangeProvider> {
+        self.resolve_for_text_pattern(|node| {
+            if !is_active.is_null() {
+                unsafe { *is_active = node.is_focused().into() };
+            }
+            if let Some(focus) = node.text_selection_focus() {
+                let range = focus.to_degenerate_range();
+                Ok(PlatformTextRange::new(&self.context, range).into())
+            } else {
+                Err(Error::empty())
+            }
+        })
+    }
+}
+
 // Ensures that `PlatformNode` is actually safe to use in the free-threaded
 // manner that we advertise via `ProviderOptions`.
 #[test]
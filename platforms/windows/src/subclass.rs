@@ -179,6 +179,12 @@ impl SubclassingAdapter {
         let mut state = self.0.state.borrow_mut();
         state.adapter.update_if_active(update_factory)
     }
+
+    /// Returns whether the window is focused, as tracked through
+    /// the focus-related messages that the subclassed window receives.
+    pub fn is_window_focused(&self) -> bool {
+        self.0.state.borrow().adapter.is_window_focused()
+    }
 }
 
 impl Drop for SubclassingAdapter {
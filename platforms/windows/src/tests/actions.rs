@@ -0,0 +1,213 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{
+    Action, ActionData, ActionRequest, ActivationHandler, NodeId, Point, Rect, Role, Toggled,
+    TreeUpdate,
+};
+use accesskit_test_utils::{MockActionHandler, TreeBuilder};
+use windows::{core::*, Win32::UI::Accessibility::*};
+
+use super::*;
+
+const WINDOW_TITLE: &str = "Actions test";
+
+const WINDOW_ID: NodeId = NodeId(0);
+const BUTTON_ID: NodeId = NodeId(1);
+const CHECK_BOX_ID: NodeId = NodeId(2);
+const SCROLL_VIEW_ID: NodeId = NodeId(3);
+const TREE_ITEM_ID: NodeId = NodeId(4);
+
+fn get_initial_state() -> TreeUpdate {
+    TreeBuilder::new(WINDOW_ID, Role::Window)
+        .child(WINDOW_ID, BUTTON_ID, Role::Button, |b| {
+            b.set_name("Submit");
+            b.add_action(Action::Default);
+            b.add_action(Action::ShowContextMenu);
+        })
+        .child(WINDOW_ID, CHECK_BOX_ID, Role::CheckBox, |b| {
+            b.set_name("Remember me");
+            b.set_toggled(Toggled::True);
+            b.add_action(Action::Default);
+        })
+        .child(WINDOW_ID, SCROLL_VIEW_ID, Role::ScrollView, |b| {
+            b.set_name("Results");
+            b.set_bounds(Rect {
+                x0: 0.0,
+                y0: 0.0,
+                x1: 100.0,
+                y1: 200.0,
+            });
+            b.set_scroll_y(50.0);
+            b.set_scroll_y_max(600.0);
+            b.add_action(Action::SetScrollOffset);
+        })
+        .child(WINDOW_ID, TREE_ITEM_ID, Role::TreeItem, |b| {
+            b.set_name("Documents");
+            b.set_expanded(false);
+            b.add_action(Action::Expand);
+            b.add_action(Action::Collapse);
+        })
+        .build()
+}
+
+struct ActionsActivationHandler;
+
+impl ActivationHandler for ActionsActivationHandler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        Some(get_initial_state())
+    }
+}
+
+fn scope<F>(f: F) -> Result<()>
+where
+    F: FnOnce(&Scope, &MockActionHandler) -> Result<()>,
+{
+    let action_handler = MockActionHandler::new();
+    super::scope(
+        WINDOW_TITLE,
+        ActionsActivationHandler {},
+        action_handler.clone(),
+        |s| f(s, &action_handler),
+    )
+}
+
+fn expect_default_action(action_handler: &MockActionHandler, target: NodeId) {
+    expect_action(
+        action_handler,
+        ActionRequest {
+            action: Action::Default,
+            target,
+            data: None,
+        },
+    );
+}
+
+#[test]
+fn invoke() -> Result<()> {
+    scope(|s, action_handler| {
+        let button = s.find_by_name("Submit")?;
+        let invoke: IUIAutomationInvokePattern =
+            unsafe { button.GetCurrentPatternAs(UIA_InvokePatternId) }?;
+        unsafe { invoke.Invoke() }?;
+        expect_default_action(action_handler, BUTTON_ID);
+        Ok(())
+    })
+}
+
+#[test]
+fn show_context_menu() -> Result<()> {
+    scope(|s, action_handler| {
+        let button: IUIAutomationElement3 = s.find_by_name("Submit")?.cast()?;
+        unsafe { button.ShowContextMenu() }?;
+        expect_action(
+            action_handler,
+            ActionRequest {
+                action: Action::ShowContextMenu,
+                target: BUTTON_ID,
+                data: None,
+            },
+        );
+        Ok(())
+    })
+}
+
+#[test]
+fn toggle() -> Result<()> {
+    scope(|s, action_handler| {
+        let check_box = s.find_by_name("Remember me")?;
+        assert!(unsafe {
+            check_box.GetCurrentPatternAs::<IUIAutomationInvokePattern>(UIA_InvokePatternId)
+        }
+        .is_err());
+        let toggle: IUIAutomationTogglePattern =
+            unsafe { check_box.GetCurrentPatternAs(UIA_TogglePatternId) }?;
+        assert_eq!(ToggleState_On, unsafe { toggle.CurrentToggleState() }?);
+        unsafe { toggle.Toggle() }?;
+        expect_default_action(action_handler, CHECK_BOX_ID);
+        Ok(())
+    })
+}
+
+fn expect_scroll_offset(action_handler: &MockActionHandler, x: f64, y: f64) {
+    expect_action(
+        action_handler,
+        ActionRequest {
+            action: Action::SetScrollOffset,
+            target: SCROLL_VIEW_ID,
+            data: Some(ActionData::SetScrollOffset(Point::new(x, y))),
+        },
+    );
+}
+
+#[test]
+fn scroll() -> Result<()> {
+    scope(|s, action_handler| {
+        let scroll_view = s.find_by_name("Results")?;
+        let scroll: IUIAutomationScrollPattern =
+            unsafe { scroll_view.GetCurrentPatternAs(UIA_ScrollPatternId) }?;
+        let vertically_scrollable: bool = unsafe { scroll.CurrentVerticallyScrollable() }?.into();
+        assert!(vertically_scrollable);
+        let horizontally_scrollable: bool =
+            unsafe { scroll.CurrentHorizontallyScrollable() }?.into();
+        assert!(!horizontally_scrollable);
+        let vertical_percent = unsafe { scroll.CurrentVerticalScrollPercent() }?;
+        assert!((vertical_percent - 50.0 / 6.0).abs() < 1e-9);
+        assert_eq!(UIA_ScrollPatternNoScroll, unsafe {
+            scroll.CurrentHorizontalScrollPercent()
+        }?);
+        assert_eq!(25.0, unsafe { scroll.CurrentVerticalViewSize() }?);
+        assert_eq!(100.0, unsafe { scroll.CurrentHorizontalViewSize() }?);
+
+        unsafe { scroll.SetScrollPercent(UIA_ScrollPatternNoScroll, 50.0) }?;
+        expect_scroll_offset(action_handler, 0.0, 300.0);
+
+        unsafe { scroll.Scroll(ScrollAmount_NoAmount, ScrollAmount_LargeIncrement) }?;
+        expect_scroll_offset(action_handler, 0.0, 250.0);
+
+        unsafe { scroll.Scroll(ScrollAmount_NoAmount, ScrollAmount_SmallDecrement) }?;
+        expect_scroll_offset(action_handler, 0.0, 30.0);
+
+        assert!(unsafe { scroll.SetScrollPercent(50.0, UIA_ScrollPatternNoScroll) }.is_err());
+        assert!(unsafe { scroll.SetScrollPercent(UIA_ScrollPatternNoScroll, 150.0) }.is_err());
+        assert!(action_handler.requests().is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn expand_collapse() -> Result<()> {
+    scope(|s, action_handler| {
+        let tree_item = s.find_by_name("Documents")?;
+        let expand_collapse: IUIAutomationExpandCollapsePattern =
+            unsafe { tree_item.GetCurrentPatternAs(UIA_ExpandCollapsePatternId) }?;
+        assert_eq!(ExpandCollapseState_Collapsed, unsafe {
+            expand_collapse.CurrentExpandCollapseState()
+        }?);
+
+        unsafe { expand_collapse.Expand() }?;
+        expect_action(
+            action_handler,
+            ActionRequest {
+                action: Action::Expand,
+                target: TREE_ITEM_ID,
+                data: None,
+            },
+        );
+
+        unsafe { expand_collapse.Collapse() }?;
+        expect_action(
+            action_handler,
+            ActionRequest {
+                action: Action::Collapse,
+                target: TREE_ITEM_ID,
+                data: None,
+            },
+        );
+
+        Ok(())
+    })
+}
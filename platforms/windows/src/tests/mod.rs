@@ -3,7 +3,8 @@
 // the LICENSE-APACHE file) or the MIT license (found in
 // the LICENSE-MIT file), at your option.
 
-use accesskit::{ActionHandler, ActivationHandler};
+use accesskit::{ActionHandler, ActionRequest, ActivationHandler, TreeUpdate};
+use accesskit_test_utils::MockActionHandler;
 use once_cell::sync::Lazy;
 use std::{
     cell::RefCell,
@@ -29,6 +30,9 @@ use super::{
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
 
+// Sent by `Scope::update_tree`, with a boxed `TreeUpdate` as the LPARAM.
+const WM_TEST_UPDATE_TREE: u32 = WM_USER;
+
 static WINDOW_CLASS_ATOM: Lazy<u16> = Lazy::new(|| {
     let class_name = w!("AccessKitTest");
 
@@ -65,6 +69,14 @@ fn update_window_focus_state(window: HWND, is_focused: bool) {
     }
 }
 
+fn update_tree(window: HWND, update: TreeUpdate) {
+    let state = unsafe { &*get_window_state(window) };
+    let mut adapter = state.adapter.borrow_mut();
+    if let Some(events) = adapter.update_if_active(|| update) {
+        events.raise();
+    }
+}
+
 struct WindowCreateParams {
     activation_handler: Box<dyn ActivationHandler>,
     action_handler: Arc<dyn ActionHandlerNoMut + Send + Sync>,
@@ -127,6 +139,11 @@ extern "system" fn wndproc(window: HWND, message: u32, wparam: WPARAM, lparam: L
             update_window_focus_state(window, false);
             LRESULT(0)
         }
+        WM_TEST_UPDATE_TREE => {
+            let update: Box<TreeUpdate> = unsafe { Box::from_raw(lparam.0 as _) };
+            update_tree(window, *update);
+            LRESULT(0)
+        }
         _ => unsafe { DefWindowProcW(window, message, wparam, lparam) },
     }
 }
@@ -174,6 +191,47 @@ impl Scope {
         unsafe { ShowWindow(self.window, SW_SHOW) };
         unsafe { SetForegroundWindow(self.window) };
     }
+
+    pub(crate) fn find_by_name(&self, name: &str) -> Result<IUIAutomationElement> {
+        let root = unsafe { self.uia.ElementFromHandle(self.window) }?;
+        let condition = unsafe {
+            self.uia
+                .CreatePropertyCondition(UIA_NamePropertyId, &VARIANT::from(name))
+        }?;
+        unsafe { root.FindFirst(TreeScope_Descendants, &condition) }
+    }
+
+    /// Applies the update on the window's thread and raises the resulting
+    /// events. The adapter ignores updates until a UIA client has
+    /// requested the tree, for example through [`Scope::find_by_name`].
+    pub(crate) fn update_tree(&self, update: TreeUpdate) {
+        let update = Box::into_raw(Box::new(update));
+        unsafe {
+            SendMessageW(
+                self.window,
+                WM_TEST_UPDATE_TREE,
+                WPARAM(0),
+                LPARAM(update as _),
+            )
+        };
+    }
+}
+
+pub(crate) fn element_name(element: &IUIAutomationElement) -> String {
+    let name = unsafe { element.CurrentName() }.unwrap();
+    name.try_into().unwrap()
+}
+
+pub(crate) fn element_names(array: &IUIAutomationElementArray) -> Result<Vec<String>> {
+    let length = unsafe { array.Length() }?;
+    (0..length)
+        .map(|i| Ok(element_name(&unsafe { array.GetElement(i) }?)))
+        .collect()
+}
+
+pub(crate) fn expect_action(action_handler: &MockActionHandler, expected: ActionRequest) {
+    let request = action_handler.wait_for_request(DEFAULT_TIMEOUT).unwrap();
+    assert_eq!(expected, request);
 }
 
 // It's not safe to run these UI-related tests concurrently.
@@ -330,5 +388,86 @@ impl IUIAutomationFocusChangedEventHandler_Impl for FocusEventHandler {
     }
 }
 
+pub(crate) struct ReceivedStructureChangedEvents {
+    mutex: Mutex<Vec<(StructureChangeType, SendableUiaElement)>>,
+    cv: Condvar,
+}
+
+impl ReceivedStructureChangedEvents {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            mutex: Mutex::new(Vec::new()),
+            cv: Condvar::new(),
+        })
+    }
+
+    /// Waits until at least the given number of events have been received,
+    /// then removes them and returns their change types along with
+    /// the names of their senders, in the order they were received.
+    pub(crate) fn wait(&self, count: usize) -> Vec<(StructureChangeType, String)> {
+        let mut received = self.mutex.lock().unwrap();
+        while received.len() < count {
+            let (lock, result) = self.cv.wait_timeout(received, DEFAULT_TIMEOUT).unwrap();
+            assert!(!result.timed_out());
+            received = lock;
+        }
+        let events = std::mem::take(&mut *received);
+        drop(received);
+        events
+            .into_iter()
+            .map(|(change_type, SendableUiaElement(sender))| (change_type, element_name(&sender)))
+            .collect()
+    }
+
+    fn put(&self, change_type: StructureChangeType, sender: IUIAutomationElement) {
+        let mut received = self.mutex.lock().unwrap();
+        received.push((change_type, SendableUiaElement(sender)));
+        self.cv.notify_one();
+    }
+}
+
+#[implement(Windows::Win32::UI::Accessibility::IUIAutomationStructureChangedEventHandler)]
+pub(crate) struct StructureChangedEventHandler {
+    received: Arc<ReceivedStructureChangedEvents>,
+}
+// See the comment on `FocusEventHandler`.
+static_assertions::assert_impl_all!(StructureChangedEventHandler: Send, Sync);
+
+impl StructureChangedEventHandler {
+    #[allow(clippy::new_ret_no_self)] // it does return self, but wrapped
+    pub(crate) fn new() -> (
+        IUIAutomationStructureChangedEventHandler,
+        Arc<ReceivedStructureChangedEvents>,
+    ) {
+        let received = ReceivedStructureChangedEvents::new();
+        (
+            Self {
+                received: Arc::clone(&received),
+            }
+            .into(),
+            received,
+        )
+    }
+}
+
+#[allow(non_snake_case)]
+impl IUIAutomationStructureChangedEventHandler_Impl for StructureChangedEventHandler {
+    fn HandleStructureChangedEvent(
+        &self,
+        sender: Option<&IUIAutomationElement>,
+        change_type: StructureChangeType,
+        _runtime_id: *const SAFEARRAY,
+    ) -> Result<()> {
+        self.received.put(change_type, sender.unwrap().clone());
+        Ok(())
+    }
+}
+
+mod actions;
+mod properties;
+mod selection;
 mod simple;
+mod structure;
 mod subclassed;
+mod table;
+mod text;
@@ -0,0 +1,78 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{ActivationHandler, Invalid, Live, NodeId, Role, TreeUpdate};
+use accesskit_test_utils::{MockActionHandler, TreeBuilder};
+use windows::{core::*, Win32::UI::Accessibility::*};
+
+use super::*;
+
+const WINDOW_TITLE: &str = "Properties test";
+
+const WINDOW_ID: NodeId = NodeId(0);
+const STATUS_ID: NodeId = NodeId(1);
+const EMAIL_ID: NodeId = NodeId(2);
+const NAME_ID: NodeId = NodeId(3);
+
+fn get_initial_state() -> TreeUpdate {
+    TreeBuilder::new(WINDOW_ID, Role::Window)
+        .child(WINDOW_ID, STATUS_ID, Role::Label, |b| {
+            b.set_name("Saved");
+            b.set_live(Live::Assertive);
+        })
+        .child(WINDOW_ID, EMAIL_ID, Role::TextInput, |b| {
+            b.set_name("Email");
+            b.set_invalid(Invalid::True);
+        })
+        .child(WINDOW_ID, NAME_ID, Role::TextInput, |b| b.set_name("Name"))
+        .build()
+}
+
+struct PropertiesActivationHandler;
+
+impl ActivationHandler for PropertiesActivationHandler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        Some(get_initial_state())
+    }
+}
+
+fn scope<F>(f: F) -> Result<()>
+where
+    F: FnOnce(&Scope) -> Result<()>,
+{
+    super::scope(
+        WINDOW_TITLE,
+        PropertiesActivationHandler {},
+        MockActionHandler::new(),
+        f,
+    )
+}
+
+fn is_data_valid_for_form(element: &IUIAutomationElement) -> Result<bool> {
+    let value = unsafe { element.GetCurrentPropertyValue(UIA_IsDataValidForFormPropertyId) }?;
+    bool::try_from(&value)
+}
+
+#[test]
+fn live_setting() -> Result<()> {
+    scope(|s| {
+        let status = s.find_by_name("Saved")?;
+        let value = unsafe { status.GetCurrentPropertyValue(UIA_LiveSettingPropertyId) }?;
+        assert_eq!(Assertive.0, i32::try_from(&value)?);
+        let email = s.find_by_name("Email")?;
+        let value = unsafe { email.GetCurrentPropertyValue(UIA_LiveSettingPropertyId) }?;
+        assert_eq!(Off.0, i32::try_from(&value)?);
+        Ok(())
+    })
+}
+
+#[test]
+fn data_valid_for_form() -> Result<()> {
+    scope(|s| {
+        assert!(!is_data_valid_for_form(&s.find_by_name("Email")?)?);
+        assert!(is_data_valid_for_form(&s.find_by_name("Name")?)?);
+        Ok(())
+    })
+}
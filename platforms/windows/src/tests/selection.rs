@@ -0,0 +1,152 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{Action, ActionRequest, ActivationHandler, NodeBuilder, NodeId, Role, TreeUpdate};
+use accesskit_test_utils::{MockActionHandler, TreeBuilder};
+use windows::{core::*, Win32::UI::Accessibility::*};
+
+use super::*;
+
+const WINDOW_TITLE: &str = "Selection test";
+
+const WINDOW_ID: NodeId = NodeId(0);
+const LIST_ID: NodeId = NodeId(1);
+const APPLE_ID: NodeId = NodeId(2);
+const BANANA_ID: NodeId = NodeId(3);
+const CHERRY_ID: NodeId = NodeId(4);
+const FOLDERS_ID: NodeId = NodeId(5);
+const DOCUMENTS_ID: NodeId = NodeId(6);
+const DOCUMENTS_GROUP_ID: NodeId = NodeId(7);
+const REPORTS_ID: NodeId = NodeId(8);
+
+fn configure_item(builder: &mut NodeBuilder, name: &str, selected: bool) {
+    builder.set_name(name);
+    builder.set_selected(selected);
+    builder.add_action(Action::Default);
+}
+
+fn get_initial_state() -> TreeUpdate {
+    TreeBuilder::new(WINDOW_ID, Role::Window)
+        .child(WINDOW_ID, LIST_ID, Role::ListBox, |b| {
+            b.set_name("Fruits");
+            b.set_multiselectable();
+        })
+        .child(LIST_ID, APPLE_ID, Role::ListItem, |b| {
+            configure_item(b, "Apple", true)
+        })
+        .child(LIST_ID, BANANA_ID, Role::ListItem, |b| {
+            configure_item(b, "Banana", false)
+        })
+        .child(LIST_ID, CHERRY_ID, Role::ListItem, |b| {
+            configure_item(b, "Cherry", true)
+        })
+        .child(WINDOW_ID, FOLDERS_ID, Role::Tree, |b| b.set_name("Folders"))
+        .child(FOLDERS_ID, DOCUMENTS_ID, Role::TreeItem, |b| {
+            configure_item(b, "Documents", false)
+        })
+        .child(DOCUMENTS_ID, DOCUMENTS_GROUP_ID, Role::Group, |_| ())
+        .child(DOCUMENTS_GROUP_ID, REPORTS_ID, Role::TreeItem, |b| {
+            configure_item(b, "Reports", true)
+        })
+        .build()
+}
+
+struct SelectionActivationHandler;
+
+impl ActivationHandler for SelectionActivationHandler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        Some(get_initial_state())
+    }
+}
+
+fn scope<F>(f: F) -> Result<()>
+where
+    F: FnOnce(&Scope, &MockActionHandler) -> Result<()>,
+{
+    let action_handler = MockActionHandler::new();
+    super::scope(
+        WINDOW_TITLE,
+        SelectionActivationHandler {},
+        action_handler.clone(),
+        |s| f(s, &action_handler),
+    )
+}
+
+#[test]
+fn selection_container() -> Result<()> {
+    scope(|s, _| {
+        let list = s.find_by_name("Fruits")?;
+        let selection: IUIAutomationSelectionPattern =
+            unsafe { list.GetCurrentPatternAs(UIA_SelectionPatternId) }?;
+        let can_select_multiple: bool = unsafe { selection.CurrentCanSelectMultiple() }?.into();
+        assert!(can_select_multiple);
+        let is_selection_required: bool = unsafe { selection.CurrentIsSelectionRequired() }?.into();
+        assert!(!is_selection_required);
+        let selected = unsafe { selection.GetCurrentSelection() }?;
+        assert_eq!(vec!["Apple", "Cherry"], element_names(&selected)?);
+        Ok(())
+    })
+}
+
+#[test]
+fn selection_items() -> Result<()> {
+    scope(|s, action_handler| {
+        let banana = s.find_by_name("Banana")?;
+        let banana_item: IUIAutomationSelectionItemPattern =
+            unsafe { banana.GetCurrentPatternAs(UIA_SelectionItemPatternId) }?;
+        let is_selected: bool = unsafe { banana_item.CurrentIsSelected() }?.into();
+        assert!(!is_selected);
+        let container = unsafe { banana_item.CurrentSelectionContainer() }?;
+        assert_eq!("Fruits", element_name(&container));
+
+        unsafe { banana_item.AddToSelection() }?;
+        expect_action(
+            action_handler,
+            ActionRequest {
+                action: Action::Default,
+                target: BANANA_ID,
+                data: None,
+            },
+        );
+
+        let apple = s.find_by_name("Apple")?;
+        let apple_item: IUIAutomationSelectionItemPattern =
+            unsafe { apple.GetCurrentPatternAs(UIA_SelectionItemPatternId) }?;
+        // Adding an item that is already selected is a no-op, so the next
+        // request that the handler sees is the removal.
+        unsafe { apple_item.AddToSelection() }?;
+        unsafe { apple_item.RemoveFromSelection() }?;
+        expect_action(
+            action_handler,
+            ActionRequest {
+                action: Action::Default,
+                target: APPLE_ID,
+                data: None,
+            },
+        );
+        assert!(action_handler.requests().is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn nested_selection_items() -> Result<()> {
+    scope(|s, _| {
+        let tree = s.find_by_name("Folders")?;
+        let selection: IUIAutomationSelectionPattern =
+            unsafe { tree.GetCurrentPatternAs(UIA_SelectionPatternId) }?;
+        let selected = unsafe { selection.GetCurrentSelection() }?;
+        assert_eq!(vec!["Reports"], element_names(&selected)?);
+
+        let reports = s.find_by_name("Reports")?;
+        let reports_item: IUIAutomationSelectionItemPattern =
+            unsafe { reports.GetCurrentPatternAs(UIA_SelectionItemPatternId) }?;
+        let container = unsafe { reports_item.CurrentSelectionContainer() }?;
+        assert_eq!("Folders", element_name(&container));
+
+        Ok(())
+    })
+}
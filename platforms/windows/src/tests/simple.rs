@@ -3,10 +3,8 @@
 // the LICENSE-APACHE file) or the MIT license (found in
 // the LICENSE-MIT file), at your option.
 
-use accesskit::{
-    Action, ActionHandler, ActionRequest, ActivationHandler, Node, NodeBuilder, NodeId, Role, Tree,
-    TreeUpdate,
-};
+use accesskit::{Action, ActivationHandler, NodeBuilder, NodeId, Role, TreeUpdate};
+use accesskit_test_utils::{MockActionHandler, TreeBuilder};
 use windows::{core::*, Win32::UI::Accessibility::*};
 
 use super::*;
@@ -17,36 +15,21 @@ const WINDOW_ID: NodeId = NodeId(0);
 const BUTTON_1_ID: NodeId = NodeId(1);
 const BUTTON_2_ID: NodeId = NodeId(2);
 
-fn make_button(name: &str) -> Node {
-    let mut builder = NodeBuilder::new(Role::Button);
+fn configure_button(builder: &mut NodeBuilder, name: &str) {
     builder.set_name(name);
     builder.add_action(Action::Focus);
-    builder.build()
 }
 
 fn get_initial_state() -> TreeUpdate {
-    let root = {
-        let mut builder = NodeBuilder::new(Role::Window);
-        builder.set_children(vec![BUTTON_1_ID, BUTTON_2_ID]);
-        builder.build()
-    };
-    let button_1 = make_button("Button 1");
-    let button_2 = make_button("Button 2");
-    TreeUpdate {
-        nodes: vec![
-            (WINDOW_ID, root),
-            (BUTTON_1_ID, button_1),
-            (BUTTON_2_ID, button_2),
-        ],
-        tree: Some(Tree::new(WINDOW_ID)),
-        focus: BUTTON_1_ID,
-    }
-}
-
-pub struct NullActionHandler;
-
-impl ActionHandler for NullActionHandler {
-    fn do_action(&mut self, _request: ActionRequest) {}
+    TreeBuilder::new(WINDOW_ID, Role::Window)
+        .child(WINDOW_ID, BUTTON_1_ID, Role::Button, |b| {
+            configure_button(b, "Button 1")
+        })
+        .child(WINDOW_ID, BUTTON_2_ID, Role::Button, |b| {
+            configure_button(b, "Button 2")
+        })
+        .focus(BUTTON_1_ID)
+        .build()
 }
 
 struct SimpleActivationHandler;
@@ -64,7 +47,7 @@ where
     super::scope(
         WINDOW_TITLE,
         SimpleActivationHandler {},
-        NullActionHandler {},
+        MockActionHandler::new(),
         f,
     )
 }
@@ -198,3 +181,46 @@ fn focus() -> Result<()> {
         Ok(())
     })
 }
+
+#[cfg(feature = "msaa")]
+#[test]
+fn msaa_bridge() -> Result<()> {
+    use windows::Win32::UI::WindowsAndMessaging::OBJID_CLIENT;
+
+    scope(|s| {
+        let mut result = std::ptr::null_mut();
+        unsafe {
+            AccessibleObjectFromWindow(
+                s.window,
+                OBJID_CLIENT.0 as u32,
+                &IAccessible::IID,
+                &mut result,
+            )
+        }?;
+        let root = unsafe { IAccessible::from_raw(result) };
+        let child_id_self = VARIANT::from(CHILDID_SELF as i32);
+
+        assert_eq!(2, unsafe { root.accChildCount() }?);
+        let role = unsafe { root.get_accRole(&child_id_self) }?;
+        assert_eq!(ROLE_SYSTEM_CLIENT as i32, i32::try_from(&role)?);
+        let name = unsafe { root.get_accName(&child_id_self) }?;
+        assert_eq!(WINDOW_TITLE, name.to_string());
+
+        let button_1: IAccessible = unsafe { root.get_accChild(&VARIANT::from(1)) }?.cast()?;
+        let name = unsafe { button_1.get_accName(&child_id_self) }?;
+        assert_eq!("Button 1", name.to_string());
+        let role = unsafe { button_1.get_accRole(&child_id_self) }?;
+        assert_eq!(ROLE_SYSTEM_PUSHBUTTON as i32, i32::try_from(&role)?);
+        let state = unsafe { button_1.get_accState(&child_id_self) }?;
+        let state = i32::try_from(&state)? as u32;
+        assert_ne!(0, state & STATE_SYSTEM_FOCUSABLE);
+
+        let parent: IAccessible = unsafe { button_1.accParent() }?.cast()?;
+        assert_eq!(2, unsafe { parent.accChildCount() }?);
+
+        let invalid_child = unsafe { button_1.get_accName(&VARIANT::from(1)) };
+        assert_eq!(Err(E_INVALIDARG.into()), invalid_child.map(|_| ()));
+
+        Ok(())
+    })
+}
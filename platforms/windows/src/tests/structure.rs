@@ -0,0 +1,121 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{ActivationHandler, NodeId, Role, TreeUpdate};
+use accesskit_test_utils::{MockActionHandler, TreeBuilder};
+use windows::{core::*, Win32::UI::Accessibility::*};
+
+use super::*;
+use crate::adapter::group_by_parent;
+
+const WINDOW_TITLE: &str = "Structure test";
+
+const WINDOW_ID: NodeId = NodeId(0);
+const LIST_ID: NodeId = NodeId(1);
+const APPLE_ID: NodeId = NodeId(2);
+const BANANA_ID: NodeId = NodeId(3);
+const CHERRY_ID: NodeId = NodeId(4);
+
+fn get_state(items: &[(NodeId, &str)]) -> TreeUpdate {
+    let mut builder =
+        TreeBuilder::new(WINDOW_ID, Role::Window)
+            .child(WINDOW_ID, LIST_ID, Role::List, |b| b.set_name("Fruits"));
+    for (id, name) in items {
+        builder = builder.child(LIST_ID, *id, Role::ListItem, |b| b.set_name(*name));
+    }
+    builder.build()
+}
+
+struct StructureActivationHandler;
+
+impl ActivationHandler for StructureActivationHandler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        Some(get_state(&[(APPLE_ID, "Apple")]))
+    }
+}
+
+fn scope<F>(f: F) -> Result<()>
+where
+    F: FnOnce(&Scope) -> Result<()>,
+{
+    super::scope(
+        WINDOW_TITLE,
+        StructureActivationHandler {},
+        MockActionHandler::new(),
+        f,
+    )
+}
+
+#[test]
+fn groups_children_by_parent() {
+    assert_eq!(
+        vec![(LIST_ID, vec![APPLE_ID])],
+        group_by_parent(&[(LIST_ID, APPLE_ID)])
+    );
+    assert_eq!(
+        vec![
+            (LIST_ID, vec![APPLE_ID, CHERRY_ID]),
+            (WINDOW_ID, vec![BANANA_ID]),
+        ],
+        group_by_parent(&[
+            (LIST_ID, APPLE_ID),
+            (WINDOW_ID, BANANA_ID),
+            (LIST_ID, CHERRY_ID),
+        ])
+    );
+    // The event for the list covers its new children.
+    assert_eq!(
+        vec![(WINDOW_ID, vec![LIST_ID])],
+        group_by_parent(&[(WINDOW_ID, LIST_ID), (LIST_ID, APPLE_ID)])
+    );
+}
+
+#[test]
+fn structure_changed_events() -> Result<()> {
+    scope(|s| {
+        // Requesting an element activates the adapter, so that it applies
+        // the updates below.
+        s.find_by_name("Apple")?;
+        let root = unsafe { s.uia.ElementFromHandle(s.window) }?;
+        let (handler, received) = StructureChangedEventHandler::new();
+        unsafe {
+            s.uia
+                .AddStructureChangedEventHandler(&root, TreeScope_Subtree, None, &handler)
+        }?;
+
+        // A single added child is reported on the child itself.
+        s.update_tree(get_state(&[(APPLE_ID, "Apple"), (BANANA_ID, "Banana")]));
+        assert_eq!(
+            vec![(StructureChangeType_ChildAdded, "Banana".into())],
+            received.wait(1)
+        );
+
+        // Siblings are reported together on their parent.
+        s.update_tree(get_state(&[
+            (APPLE_ID, "Apple"),
+            (BANANA_ID, "Banana"),
+            (CHERRY_ID, "Cherry"),
+        ]));
+        s.update_tree(get_state(&[(BANANA_ID, "Banana")]));
+        assert_eq!(
+            vec![
+                (StructureChangeType_ChildAdded, "Cherry".into()),
+                (StructureChangeType_ChildrenBulkRemoved, "Fruits".into()),
+            ],
+            received.wait(2)
+        );
+
+        // A single removed child is reported on its parent, since
+        // the child is no longer available.
+        s.update_tree(get_state(&[]));
+        assert_eq!(
+            vec![(StructureChangeType_ChildRemoved, "Fruits".into())],
+            received.wait(1)
+        );
+
+        unsafe { s.uia.RemoveStructureChangedEventHandler(&root, &handler) }?;
+        Ok(())
+    })
+}
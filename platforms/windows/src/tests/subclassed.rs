@@ -3,11 +3,15 @@
 // the LICENSE-APACHE file) or the MIT license (found in
 // the LICENSE-MIT file), at your option.
 
-use accesskit::{
-    Action, ActionHandler, ActionRequest, ActivationHandler, Node, NodeBuilder, NodeId, Role, Tree,
-    TreeUpdate,
+use accesskit::{Action, ActivationHandler, NodeBuilder, NodeId, Role, TreeUpdate};
+use accesskit_test_utils::{MockActionHandler, TreeBuilder};
+use windows::Win32::{
+    Foundation::*,
+    UI::{
+        Accessibility::*,
+        WindowsAndMessaging::{SendMessageW, WM_KILLFOCUS, WM_SETFOCUS},
+    },
 };
-use windows::Win32::{Foundation::*, UI::Accessibility::*};
 use winit::{
     application::ApplicationHandler,
     event::WindowEvent,
@@ -26,37 +30,22 @@ const WINDOW_ID: NodeId = NodeId(0);
 const BUTTON_1_ID: NodeId = NodeId(1);
 const BUTTON_2_ID: NodeId = NodeId(2);
 
-fn make_button(name: &str) -> Node {
-    let mut builder = NodeBuilder::new(Role::Button);
+fn configure_button(builder: &mut NodeBuilder, name: &str) {
     builder.set_name(name);
     builder.add_action(Action::Focus);
-    builder.build()
 }
 
 fn get_initial_state() -> TreeUpdate {
-    let root = {
-        let mut builder = NodeBuilder::new(Role::Window);
-        builder.set_children(vec![BUTTON_1_ID, BUTTON_2_ID]);
-        builder.set_name(WINDOW_TITLE);
-        builder.build()
-    };
-    let button_1 = make_button("Button 1");
-    let button_2 = make_button("Button 2");
-    TreeUpdate {
-        nodes: vec![
-            (WINDOW_ID, root),
-            (BUTTON_1_ID, button_1),
-            (BUTTON_2_ID, button_2),
-        ],
-        tree: Some(Tree::new(WINDOW_ID)),
-        focus: BUTTON_1_ID,
-    }
-}
-
-pub struct NullActionHandler;
-
-impl ActionHandler for NullActionHandler {
-    fn do_action(&mut self, _request: ActionRequest) {}
+    TreeBuilder::new(WINDOW_ID, Role::Window)
+        .configure(WINDOW_ID, |b| b.set_name(WINDOW_TITLE))
+        .child(WINDOW_ID, BUTTON_1_ID, Role::Button, |b| {
+            configure_button(b, "Button 1")
+        })
+        .child(WINDOW_ID, BUTTON_2_ID, Role::Button, |b| {
+            configure_button(b, "Button 2")
+        })
+        .focus(BUTTON_1_ID)
+        .build()
 }
 
 struct SimpleActivationHandler;
@@ -87,8 +76,16 @@ impl ApplicationHandler<()> for TestApplication {
             _ => unreachable!(),
         };
         let adapter =
-            SubclassingAdapter::new(hwnd, SimpleActivationHandler {}, NullActionHandler {});
+            SubclassingAdapter::new(hwnd, SimpleActivationHandler {}, MockActionHandler::new());
         assert!(unsafe { UiaHasServerSideProvider(hwnd) }.as_bool());
+        // The window was never shown, so it never received focus.
+        assert!(!adapter.is_window_focused());
+        // Focus and blur the window by sending the messages that
+        // the subclassed window procedure tracks.
+        unsafe { SendMessageW(hwnd, WM_SETFOCUS, WPARAM(0), LPARAM(0)) };
+        assert!(adapter.is_window_focused());
+        unsafe { SendMessageW(hwnd, WM_KILLFOCUS, WPARAM(0), LPARAM(0)) };
+        assert!(!adapter.is_window_focused());
         drop(window);
         drop(adapter);
         event_loop.exit();
@@ -0,0 +1,127 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{ActivationHandler, NodeId, Role, TreeUpdate};
+use accesskit_test_utils::{MockActionHandler, TreeBuilder};
+use windows::{core::*, Win32::UI::Accessibility::*};
+
+use super::*;
+
+const WINDOW_TITLE: &str = "Table test";
+
+const WINDOW_ID: NodeId = NodeId(0);
+const TABLE_ID: NodeId = NodeId(1);
+const HEADER_ROW_ID: NodeId = NodeId(2);
+const NAME_HEADER_ID: NodeId = NodeId(3);
+const SCORE_HEADER_ID: NodeId = NodeId(4);
+const ROW_1_ID: NodeId = NodeId(5);
+const ROW_1_HEADER_ID: NodeId = NodeId(6);
+const ROW_1_SCORE_ID: NodeId = NodeId(7);
+const ROW_2_ID: NodeId = NodeId(8);
+const ROW_2_HEADER_ID: NodeId = NodeId(9);
+const ROW_2_SCORE_ID: NodeId = NodeId(10);
+
+fn get_initial_state() -> TreeUpdate {
+    TreeBuilder::new(WINDOW_ID, Role::Window)
+        .child(WINDOW_ID, TABLE_ID, Role::Table, |b| b.set_name("Scores"))
+        .child(TABLE_ID, HEADER_ROW_ID, Role::Row, |_| ())
+        .child(HEADER_ROW_ID, NAME_HEADER_ID, Role::ColumnHeader, |b| {
+            b.set_name("Name")
+        })
+        .child(HEADER_ROW_ID, SCORE_HEADER_ID, Role::ColumnHeader, |b| {
+            b.set_name("Score")
+        })
+        .child(TABLE_ID, ROW_1_ID, Role::Row, |_| ())
+        .child(ROW_1_ID, ROW_1_HEADER_ID, Role::RowHeader, |b| {
+            b.set_name("Alice")
+        })
+        .child(ROW_1_ID, ROW_1_SCORE_ID, Role::Cell, |b| b.set_name("10"))
+        .child(TABLE_ID, ROW_2_ID, Role::Row, |_| ())
+        .child(ROW_2_ID, ROW_2_HEADER_ID, Role::RowHeader, |b| {
+            b.set_name("Bob")
+        })
+        .child(ROW_2_ID, ROW_2_SCORE_ID, Role::Cell, |b| b.set_name("7"))
+        .build()
+}
+
+struct TableActivationHandler;
+
+impl ActivationHandler for TableActivationHandler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        Some(get_initial_state())
+    }
+}
+
+fn scope<F>(f: F) -> Result<()>
+where
+    F: FnOnce(&Scope) -> Result<()>,
+{
+    super::scope(
+        WINDOW_TITLE,
+        TableActivationHandler {},
+        MockActionHandler::new(),
+        f,
+    )
+}
+
+#[test]
+fn grid_and_table_patterns() -> Result<()> {
+    scope(|s| {
+        let table = s.find_by_name("Scores")?;
+
+        let grid: IUIAutomationGridPattern =
+            unsafe { table.GetCurrentPatternAs(UIA_GridPatternId) }?;
+        assert_eq!(3, unsafe { grid.CurrentRowCount() }?);
+        assert_eq!(2, unsafe { grid.CurrentColumnCount() }?);
+        let cell = unsafe { grid.GetItem(2, 1) }?;
+        assert_eq!("7", element_name(&cell));
+        assert!(unsafe { grid.GetItem(3, 0) }.is_err());
+
+        let table_pattern: IUIAutomationTablePattern =
+            unsafe { table.GetCurrentPatternAs(UIA_TablePatternId) }?;
+        assert_eq!(RowOrColumnMajor_RowMajor, unsafe {
+            table_pattern.CurrentRowOrColumnMajor()
+        }?);
+        let row_headers = unsafe { table_pattern.GetCurrentRowHeaders() }?;
+        assert_eq!(vec!["Alice", "Bob"], element_names(&row_headers)?);
+        let column_headers = unsafe { table_pattern.GetCurrentColumnHeaders() }?;
+        assert_eq!(vec!["Name", "Score"], element_names(&column_headers)?);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn grid_item_and_table_item_patterns() -> Result<()> {
+    scope(|s| {
+        let cell = s.find_by_name("10")?;
+
+        let grid_item: IUIAutomationGridItemPattern =
+            unsafe { cell.GetCurrentPatternAs(UIA_GridItemPatternId) }?;
+        assert_eq!(1, unsafe { grid_item.CurrentRow() }?);
+        assert_eq!(1, unsafe { grid_item.CurrentColumn() }?);
+        assert_eq!(1, unsafe { grid_item.CurrentRowSpan() }?);
+        assert_eq!(1, unsafe { grid_item.CurrentColumnSpan() }?);
+        let containing_grid = unsafe { grid_item.CurrentContainingGrid() }?;
+        assert_eq!("Scores", element_name(&containing_grid));
+
+        let table_item: IUIAutomationTableItemPattern =
+            unsafe { cell.GetCurrentPatternAs(UIA_TableItemPatternId) }?;
+        let row_headers = unsafe { table_item.GetCurrentRowHeaderItems() }?;
+        assert_eq!(vec!["Alice"], element_names(&row_headers)?);
+        let column_headers = unsafe { table_item.GetCurrentColumnHeaderItems() }?;
+        assert_eq!(vec!["Score"], element_names(&column_headers)?);
+
+        let row = s.find_by_name("Alice")?;
+        let walker = unsafe { s.uia.RawViewWalker() }?;
+        let row = unsafe { walker.GetParentElement(&row) }?;
+        assert!(unsafe {
+            row.GetCurrentPatternAs::<IUIAutomationGridItemPattern>(UIA_GridItemPatternId)
+        }
+        .is_err());
+
+        Ok(())
+    })
+}
@@ -0,0 +1,76 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{Action, ActivationHandler, NodeId, Role, TextPosition, TextSelection, TreeUpdate};
+use accesskit_test_utils::{MockActionHandler, TreeBuilder};
+use windows::{
+    core::*,
+    Win32::{Foundation::BOOL, UI::Accessibility::*},
+};
+
+use super::*;
+
+const WINDOW_TITLE: &str = "Text test";
+
+const WINDOW_ID: NodeId = NodeId(0);
+const TEXT_INPUT_ID: NodeId = NodeId(1);
+const RUN_ID: NodeId = NodeId(2);
+
+fn get_initial_state() -> TreeUpdate {
+    TreeBuilder::new(WINDOW_ID, Role::Window)
+        .child(WINDOW_ID, TEXT_INPUT_ID, Role::TextInput, |b| {
+            b.set_name("Message");
+            b.add_action(Action::Focus);
+            let caret = TextPosition {
+                node: RUN_ID,
+                character_index: 3,
+            };
+            b.set_text_selection(TextSelection {
+                anchor: caret,
+                focus: caret,
+            });
+        })
+        .child(TEXT_INPUT_ID, RUN_ID, Role::InlineTextBox, |b| {
+            b.set_value("Hello");
+            b.set_character_lengths([1, 1, 1, 1, 1]);
+        })
+        .focus(TEXT_INPUT_ID)
+        .build()
+}
+
+struct TextActivationHandler;
+
+impl ActivationHandler for TextActivationHandler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        Some(get_initial_state())
+    }
+}
+
+fn scope<F>(f: F) -> Result<()>
+where
+    F: FnOnce(&Scope) -> Result<()>,
+{
+    super::scope(
+        WINDOW_TITLE,
+        TextActivationHandler {},
+        MockActionHandler::new(),
+        f,
+    )
+}
+
+#[test]
+fn caret_range() -> Result<()> {
+    scope(|s| {
+        let text_input = s.find_by_name("Message")?;
+        let text: IUIAutomationTextPattern2 =
+            unsafe { text_input.GetCurrentPatternAs(UIA_TextPattern2Id) }?;
+        let mut is_active = BOOL::default();
+        let caret = unsafe { text.GetCaretRange(&mut is_active) }?;
+        assert_eq!("", unsafe { caret.GetText(-1) }?.to_string());
+        unsafe { caret.ExpandToEnclosingUnit(TextUnit_Character) }?;
+        assert_eq!("l", unsafe { caret.GetText(-1) }?.to_string());
+        Ok(())
+    })
+}
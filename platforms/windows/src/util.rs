@@ -101,6 +101,18 @@ impl From<OrientationType> for Variant {
     }
 }
 
+impl From<ExpandCollapseState> for Variant {
+    fn from(value: ExpandCollapseState) -> Self {
+        Self(value.0.into())
+    }
+}
+
+impl From<RowOrColumnMajor> for Variant {
+    fn from(value: RowOrColumnMajor) -> Self {
+        Self(value.0.into())
+    }
+}
+
 impl From<bool> for Variant {
     fn from(value: bool) -> Self {
         Self(value.into())
@@ -156,6 +168,11 @@ pub(crate) enum QueuedEvent {
         old_value: VARIANT,
         new_value: VARIANT,
     },
+    StructureChanged {
+        element: IRawElementProviderSimple,
+        change_type: StructureChangeType,
+        runtime_id: Vec<i32>,
+    },
 }
 
 pub(crate) fn not_implemented() -> Error {
@@ -1,6 +1,6 @@
 use accesskit::{
-    Action, ActionRequest, DefaultActionVerb, Live, Node, NodeBuilder, NodeId, Rect, Role, Tree,
-    TreeUpdate,
+    Action, ActionRequest, DefaultActionVerb, Live, Node, NodeBuilder, NodeId, Rect, Role,
+    TreeUpdate, TreeUpdateBuilder,
 };
 use accesskit_winit::{Adapter, Event as AccessKitEvent, WindowEvent as AccessKitWindowEvent};
 use std::error::Error;
@@ -83,32 +83,22 @@ impl UiState {
         let root = self.build_root();
         let button_1 = build_button(BUTTON_1_ID, "Button 1");
         let button_2 = build_button(BUTTON_2_ID, "Button 2");
-        let mut tree = Tree::new(WINDOW_ID);
-        tree.app_name = Some("simple".to_string());
-        let mut result = TreeUpdate {
-            nodes: vec![
-                (WINDOW_ID, root),
-                (BUTTON_1_ID, button_1),
-                (BUTTON_2_ID, button_2),
-            ],
-            tree: Some(tree),
-            focus: self.focus,
-        };
+        let mut builder = TreeUpdateBuilder::new()
+            .tree_root(WINDOW_ID)
+            .with_app_name("simple")
+            .node(WINDOW_ID, root)
+            .node(BUTTON_1_ID, button_1)
+            .node(BUTTON_2_ID, button_2)
+            .focus(self.focus);
         if let Some(announcement) = &self.announcement {
-            result
-                .nodes
-                .push((ANNOUNCEMENT_ID, build_announcement(announcement)));
+            builder = builder.node(ANNOUNCEMENT_ID, build_announcement(announcement));
         }
-        result
+        builder.build()
     }
 
     fn set_focus(&mut self, adapter: &mut Adapter, focus: NodeId) {
         self.focus = focus;
-        adapter.update_if_active(|| TreeUpdate {
-            nodes: vec![],
-            tree: None,
-            focus,
-        });
+        adapter.update_if_active(|| TreeUpdateBuilder::new().focus(focus).build());
     }
 
     fn press_button(&mut self, adapter: &mut Adapter, id: NodeId) {
@@ -121,11 +111,11 @@ impl UiState {
         adapter.update_if_active(|| {
             let announcement = build_announcement(text);
             let root = self.build_root();
-            TreeUpdate {
-                nodes: vec![(ANNOUNCEMENT_ID, announcement), (WINDOW_ID, root)],
-                tree: None,
-                focus: self.focus,
-            }
+            TreeUpdateBuilder::new()
+                .node(ANNOUNCEMENT_ID, announcement)
+                .node(WINDOW_ID, root)
+                .focus(self.focus)
+                .build()
         });
     }
 }
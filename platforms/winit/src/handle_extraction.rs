@@ -0,0 +1,49 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file).
+
+// Extracts the native handles that the platform adapters need from
+// a winit window, regardless of which raw-window-handle version
+// is enabled. There's no display handle helper, because none of the
+// adapters use the display connection.
+
+#[cfg(feature = "rwh_05")]
+use crate::raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+#[cfg(feature = "rwh_06")]
+use crate::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+use winit::window::Window;
+
+#[cfg(feature = "rwh_05")]
+pub(crate) fn get_window_handle(window: &Window) -> RawWindowHandle {
+    window.raw_window_handle()
+}
+
+#[cfg(feature = "rwh_06")]
+pub(crate) fn get_window_handle(window: &Window) -> RawWindowHandle {
+    window.window_handle().unwrap().as_raw()
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn get_hwnd(window: &Window) -> isize {
+    match get_window_handle(window) {
+        #[cfg(feature = "rwh_05")]
+        RawWindowHandle::Win32(handle) => handle.hwnd as isize,
+        #[cfg(feature = "rwh_06")]
+        RawWindowHandle::Win32(handle) => handle.hwnd.get(),
+        RawWindowHandle::WinRt(_) => unimplemented!(),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn get_ns_view(window: &Window) -> *mut std::ffi::c_void {
+    match get_window_handle(window) {
+        #[cfg(feature = "rwh_05")]
+        RawWindowHandle::AppKit(handle) => handle.ns_view,
+        #[cfg(feature = "rwh_06")]
+        RawWindowHandle::AppKit(handle) => handle.ns_view.as_ptr(),
+        RawWindowHandle::UiKit(_) => unimplemented!(),
+        _ => unreachable!(),
+    }
+}
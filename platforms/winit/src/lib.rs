@@ -63,6 +63,8 @@ use rwh_05 as raw_window_handle;
 #[allow(unused)]
 use rwh_06 as raw_window_handle;
 
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+mod handle_extraction;
 mod platform_impl;
 
 #[derive(Debug)]
@@ -240,4 +242,11 @@ impl Adapter {
     pub fn update_if_active(&mut self, updater: impl FnOnce() -> TreeUpdate) {
         self.inner.update_if_active(updater);
     }
+
+    /// Returns whether the window is focused, as last reported through
+    /// the window events passed to [`Adapter::process_event`] (or, on
+    /// Windows, the window messages that the adapter observes directly).
+    pub fn is_window_focused(&self) -> bool {
+        self.inner.is_window_focused()
+    }
 }
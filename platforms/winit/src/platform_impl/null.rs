@@ -5,7 +5,9 @@
 use accesskit::{ActionHandler, ActivationHandler, DeactivationHandler, TreeUpdate};
 use winit::{event::WindowEvent, window::Window};
 
-pub struct Adapter;
+pub struct Adapter {
+    is_window_focused: bool,
+}
 
 impl Adapter {
     pub fn new(
@@ -14,10 +16,20 @@ impl Adapter {
         _action_handler: impl 'static + ActionHandler,
         _deactivation_handler: impl 'static + DeactivationHandler,
     ) -> Self {
-        Self {}
+        Self {
+            is_window_focused: false,
+        }
     }
 
     pub fn update_if_active(&mut self, _updater: impl FnOnce() -> TreeUpdate) {}
 
-    pub fn process_event(&mut self, _window: &Window, _event: &WindowEvent) {}
+    pub fn is_window_focused(&self) -> bool {
+        self.is_window_focused
+    }
+
+    pub fn process_event(&mut self, _window: &Window, event: &WindowEvent) {
+        if let WindowEvent::Focused(is_focused) = event {
+            self.is_window_focused = *is_focused;
+        }
+    }
 }
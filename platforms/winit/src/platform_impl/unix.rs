@@ -33,6 +33,10 @@ impl Adapter {
         self.adapter.update_window_focus_state(is_focused);
     }
 
+    pub fn is_window_focused(&self) -> bool {
+        self.adapter.is_window_focused()
+    }
+
     pub fn process_event(&mut self, window: &Window, event: &WindowEvent) {
         match event {
             WindowEvent::Moved(outer_position) => {
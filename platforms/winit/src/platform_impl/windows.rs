@@ -1,15 +1,13 @@
 // Copyright 2022 The AccessKit Authors. All rights reserved.
 // Licensed under the Apache License, Version 2.0 (found in
 // the LICENSE-APACHE file).
-#[cfg(feature = "rwh_05")]
-use crate::raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
-#[cfg(feature = "rwh_06")]
-use crate::raw_window_handle::{HasWindowHandle, RawWindowHandle};
 
 use accesskit::{ActionHandler, ActivationHandler, DeactivationHandler, TreeUpdate};
 use accesskit_windows::{SubclassingAdapter, HWND};
 use winit::{event::WindowEvent, window::Window};
 
+use crate::handle_extraction::get_hwnd;
+
 pub struct Adapter {
     adapter: SubclassingAdapter,
 }
@@ -21,19 +19,7 @@ impl Adapter {
         action_handler: impl 'static + ActionHandler + Send,
         _deactivation_handler: impl 'static + DeactivationHandler,
     ) -> Self {
-        #[cfg(feature = "rwh_05")]
-        let hwnd = match window.raw_window_handle() {
-            RawWindowHandle::Win32(handle) => handle.hwnd as isize,
-            RawWindowHandle::WinRt(_) => unimplemented!(),
-            _ => unreachable!(),
-        };
-        #[cfg(feature = "rwh_06")]
-        let hwnd = match window.window_handle().unwrap().as_raw() {
-            RawWindowHandle::Win32(handle) => handle.hwnd.get(),
-            RawWindowHandle::WinRt(_) => unimplemented!(),
-            _ => unreachable!(),
-        };
-
+        let hwnd = get_hwnd(window);
         let adapter = SubclassingAdapter::new(HWND(hwnd), activation_handler, action_handler);
         Self { adapter }
     }
@@ -44,5 +30,9 @@ impl Adapter {
         }
     }
 
+    pub fn is_window_focused(&self) -> bool {
+        self.adapter.is_window_focused()
+    }
+
     pub fn process_event(&mut self, _window: &Window, _event: &WindowEvent) {}
 }
@@ -0,0 +1,101 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{ActionHandler, ActionRequest};
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+
+#[derive(Default)]
+struct Shared {
+    requests: Mutex<Vec<ActionRequest>>,
+    cv: Condvar,
+}
+
+/// An [`ActionHandler`] that records the requests it receives.
+///
+/// Clones share the same list of requests, so a test can keep a clone
+/// while passing the handler to an adapter. Requests may arrive
+/// on any thread.
+#[derive(Clone, Default)]
+pub struct MockActionHandler {
+    shared: Arc<Shared>,
+}
+
+impl MockActionHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<ActionRequest> {
+        self.shared.requests.lock().unwrap().clone()
+    }
+
+    /// Removes and returns the requests received so far, oldest first.
+    pub fn take_requests(&self) -> Vec<ActionRequest> {
+        std::mem::take(&mut *self.shared.requests.lock().unwrap())
+    }
+
+    /// Removes and returns the oldest request, waiting up to the given
+    /// timeout for one to arrive.
+    pub fn wait_for_request(&self, timeout: Duration) -> Option<ActionRequest> {
+        let requests = self.shared.requests.lock().unwrap();
+        let (mut requests, _) = self
+            .shared
+            .cv
+            .wait_timeout_while(requests, timeout, |requests| requests.is_empty())
+            .unwrap();
+        (!requests.is_empty()).then(|| requests.remove(0))
+    }
+}
+
+impl ActionHandler for MockActionHandler {
+    fn do_action(&mut self, request: ActionRequest) {
+        self.shared.requests.lock().unwrap().push(request);
+        self.shared.cv.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{Action, ActionHandler, ActionRequest, NodeId};
+    use std::{thread, time::Duration};
+
+    use super::MockActionHandler;
+
+    fn request(target: u64) -> ActionRequest {
+        ActionRequest {
+            action: Action::Default,
+            target: NodeId(target),
+            data: None,
+        }
+    }
+
+    #[test]
+    fn records_requests_across_clones() {
+        let handler = MockActionHandler::new();
+        let mut clone = handler.clone();
+        clone.do_action(request(1));
+        clone.do_action(request(2));
+        assert_eq!(vec![request(1), request(2)], handler.requests());
+        assert_eq!(vec![request(1), request(2)], handler.take_requests());
+        assert!(handler.requests().is_empty());
+    }
+
+    #[test]
+    fn wait_for_request_from_another_thread() {
+        let handler = MockActionHandler::new();
+        let mut clone = handler.clone();
+        let sender = thread::spawn(move || clone.do_action(request(1)));
+        assert_eq!(
+            Some(request(1)),
+            handler.wait_for_request(Duration::from_secs(5))
+        );
+        sender.join().unwrap();
+        assert_eq!(None, handler.wait_for_request(Duration::ZERO));
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{NodeId, TreeUpdate};
+use accesskit_consumer::{Node, Tree, TreeChangeHandler, TreeState};
+use std::{
+    sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+    time::Duration,
+};
+
+/// A change reported by the consumer library while the mock adapter
+/// applied an update, as a real adapter would translate it into
+/// a platform event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MockEvent {
+    NodeAdded(NodeId),
+    NodeUpdated(NodeId),
+    NodeRemoved(NodeId),
+    BusyStateChanged(NodeId, bool),
+    FocusMoved {
+        old: Option<NodeId>,
+        new: Option<NodeId>,
+    },
+    TitleChanged(Option<String>),
+    ActiveDescendantChanged {
+        focus: NodeId,
+        old: Option<NodeId>,
+        new: Option<NodeId>,
+    },
+}
+
+struct EventRecorder<'a>(&'a mut Vec<MockEvent>);
+
+impl TreeChangeHandler for EventRecorder<'_> {
+    fn node_added(&mut self, node: &Node) {
+        self.0.push(MockEvent::NodeAdded(node.id()));
+    }
+
+    fn node_updated(&mut self, _old_node: &Node, new_node: &Node) {
+        self.0.push(MockEvent::NodeUpdated(new_node.id()));
+    }
+
+    fn focus_moved(&mut self, old_node: Option<&Node>, new_node: Option<&Node>) {
+        self.0.push(MockEvent::FocusMoved {
+            old: old_node.map(|node| node.id()),
+            new: new_node.map(|node| node.id()),
+        });
+    }
+
+    fn node_removed(&mut self, node: &Node) {
+        self.0.push(MockEvent::NodeRemoved(node.id()));
+    }
+
+    fn busy_state_changed(&mut self, node: &Node) {
+        self.0
+            .push(MockEvent::BusyStateChanged(node.id(), node.is_busy()));
+    }
+
+    fn title_changed(&mut self, title: Option<&str>) {
+        self.0
+            .push(MockEvent::TitleChanged(title.map(String::from)));
+    }
+
+    fn active_descendant_changed(
+        &mut self,
+        focus: &Node,
+        old_node: Option<&Node>,
+        new_node: Option<&Node>,
+    ) {
+        self.0.push(MockEvent::ActiveDescendantChanged {
+            focus: focus.id(),
+            old: old_node.map(|node| node.id()),
+            new: new_node.map(|node| node.id()),
+        });
+    }
+}
+
+/// Simulates the lifecycle of a platform adapter without a window.
+///
+/// Like a real adapter, it owns the tree and applies the updates that
+/// the application sends it, through the [`Sender`] returned by
+/// [`spawn_mock_adapter`], recording the resulting changes as
+/// [`MockEvent`]s. Updates are only applied when the test asks for them,
+/// which stands in for the adapter's event loop.
+pub struct MockAdapter {
+    tree: Tree,
+    updates: Receiver<TreeUpdate>,
+    events: Vec<MockEvent>,
+}
+
+impl MockAdapter {
+    fn apply(&mut self, update: TreeUpdate) {
+        let mut recorder = EventRecorder(&mut self.events);
+        self.tree.update_and_process_changes(update, &mut recorder);
+    }
+
+    /// Applies all updates that have already been sent, and returns
+    /// how many there were.
+    pub fn process_updates(&mut self) -> usize {
+        let mut count = 0;
+        while let Ok(update) = self.updates.try_recv() {
+            self.apply(update);
+            count += 1;
+        }
+        count
+    }
+
+    /// Waits up to the given timeout for an update to be sent, then
+    /// applies it along with any others that are pending. Returns how many
+    /// updates were applied, which is zero if the timeout elapsed
+    /// or all senders were dropped.
+    pub fn wait_for_updates(&mut self, timeout: Duration) -> usize {
+        match self.updates.recv_timeout(timeout) {
+            Ok(update) => {
+                self.apply(update);
+                1 + self.process_updates()
+            }
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => 0,
+        }
+    }
+
+    pub fn update_window_focus_state(&mut self, is_focused: bool) {
+        let mut recorder = EventRecorder(&mut self.events);
+        self.tree
+            .update_host_focus_state_and_process_changes(is_focused, &mut recorder);
+    }
+
+    pub fn state(&self) -> &TreeState {
+        self.tree.state()
+    }
+
+    /// Removes and returns the events recorded so far, oldest first.
+    pub fn take_events(&mut self) -> Vec<MockEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Creates a [`MockAdapter`] with the given initial tree, in an unfocused
+/// window, along with a sender through which the application side
+/// of a test, possibly on another thread, can send it updates.
+pub fn spawn_mock_adapter(initial: TreeUpdate) -> (MockAdapter, Sender<TreeUpdate>) {
+    let (sender, updates) = channel();
+    let adapter = MockAdapter {
+        tree: Tree::new(initial, false),
+        updates,
+        events: Vec::new(),
+    };
+    (adapter, sender)
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{NodeBuilder, NodeId, Role, TreeUpdate, TreeUpdateBuilder};
+    use std::{thread, time::Duration};
+
+    use super::{spawn_mock_adapter, MockEvent};
+    use crate::TreeBuilder;
+
+    fn initial_tree() -> TreeUpdate {
+        TreeBuilder::new(NodeId(0), Role::Window)
+            .child(NodeId(0), NodeId(1), Role::Button, |b| b.set_name("OK"))
+            .focus(NodeId(1))
+            .build()
+    }
+
+    #[test]
+    fn applies_updates_when_asked() {
+        let (mut adapter, sender) = spawn_mock_adapter(initial_tree());
+        assert_eq!(0, adapter.process_updates());
+        let mut button = NodeBuilder::new(Role::Button);
+        button.set_name("Cancel");
+        sender
+            .send(
+                TreeUpdateBuilder::new()
+                    .node(NodeId(1), button.build())
+                    .focus(NodeId(1))
+                    .build(),
+            )
+            .unwrap();
+        assert_eq!(
+            Some("OK".into()),
+            adapter.state().node_by_id(NodeId(1)).unwrap().name()
+        );
+        assert_eq!(1, adapter.process_updates());
+        assert_eq!(
+            Some("Cancel".into()),
+            adapter.state().node_by_id(NodeId(1)).unwrap().name()
+        );
+        assert_eq!(
+            vec![MockEvent::NodeUpdated(NodeId(1))],
+            adapter.take_events()
+        );
+    }
+
+    #[test]
+    fn updates_from_another_thread() {
+        let (mut adapter, sender) = spawn_mock_adapter(initial_tree());
+        let app = thread::spawn(move || {
+            let update = TreeBuilder::new(NodeId(0), Role::Window)
+                .child(NodeId(0), NodeId(1), Role::Button, |b| b.set_name("OK"))
+                .child(NodeId(0), NodeId(2), Role::Button, |b| b.set_name("Cancel"))
+                .focus(NodeId(2))
+                .build();
+            sender.send(update).unwrap();
+        });
+        assert_eq!(1, adapter.wait_for_updates(Duration::from_secs(5)));
+        app.join().unwrap();
+        crate::assert_tree_matches!(adapter.state(), Window {
+            Button("OK"),
+            Button("Cancel"),
+        });
+        // The window isn't focused, so moving the tree's focus
+        // doesn't produce a focus event.
+        let events = adapter.take_events();
+        assert_eq!(2, events.len());
+        assert!(events.contains(&MockEvent::NodeAdded(NodeId(2))));
+        assert!(events.contains(&MockEvent::NodeUpdated(NodeId(0))));
+        adapter.update_window_focus_state(true);
+        assert!(adapter.take_events().contains(&MockEvent::FocusMoved {
+            old: None,
+            new: Some(NodeId(2)),
+        }));
+        // All senders are gone.
+        assert_eq!(0, adapter.wait_for_updates(Duration::ZERO));
+    }
+
+    #[test]
+    fn records_busy_state_changes() {
+        let (mut adapter, sender) = spawn_mock_adapter(initial_tree());
+        let update = |busy| {
+            let mut button = NodeBuilder::new(Role::Button);
+            button.set_name("OK");
+            if busy {
+                button.set_busy();
+            }
+            TreeUpdateBuilder::new()
+                .node(NodeId(1), button.build())
+                .focus(NodeId(1))
+                .build()
+        };
+        sender.send(update(true)).unwrap();
+        assert_eq!(1, adapter.process_updates());
+        assert_eq!(
+            vec![
+                MockEvent::NodeUpdated(NodeId(1)),
+                MockEvent::BusyStateChanged(NodeId(1), true),
+            ],
+            adapter.take_events()
+        );
+        sender.send(update(false)).unwrap();
+        assert_eq!(1, adapter.process_updates());
+        assert_eq!(
+            vec![
+                MockEvent::NodeUpdated(NodeId(1)),
+                MockEvent::BusyStateChanged(NodeId(1), false),
+            ],
+            adapter.take_events()
+        );
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+mod action_handler;
+pub use action_handler::MockActionHandler;
+
+mod adapter;
+pub use adapter::{spawn_mock_adapter, MockAdapter, MockEvent};
+
+mod matcher;
+pub use matcher::{tree_matches, ExpectedNode};
+
+mod tree_builder;
+pub use tree_builder::TreeBuilder;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use accesskit::Role;
+}
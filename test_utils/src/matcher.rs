@@ -0,0 +1,196 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::Role;
+use accesskit_consumer::{Node, TreeState};
+
+/// The expected role, name, and children of a node, for use with
+/// [`tree_matches`]. This is usually built with the
+/// [`assert_tree_matches`](crate::assert_tree_matches) macro.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpectedNode {
+    pub role: Role,
+    /// If `None`, the node's name isn't checked.
+    pub name: Option<String>,
+    pub children: Vec<ExpectedNode>,
+}
+
+impl ExpectedNode {
+    pub fn new(role: Role) -> Self {
+        Self {
+            role,
+            name: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn children(mut self, children: impl Into<Vec<ExpectedNode>>) -> Self {
+        self.children = children.into();
+        self
+    }
+}
+
+fn node_matches(node: &Node, expected: &ExpectedNode, path: &str) -> Result<(), String> {
+    if node.role() != expected.role {
+        return Err(format!(
+            "{path}: expected role {:?}, found {:?}",
+            expected.role,
+            node.role()
+        ));
+    }
+    if let Some(expected_name) = &expected.name {
+        let name = node.name();
+        if name.as_deref() != Some(expected_name.as_str()) {
+            return Err(format!(
+                "{path}: expected name {expected_name:?}, found {name:?}"
+            ));
+        }
+    }
+    let children = node.children().collect::<Vec<_>>();
+    if children.len() != expected.children.len() {
+        return Err(format!(
+            "{path}: expected {} children, found {}",
+            expected.children.len(),
+            children.len()
+        ));
+    }
+    for (i, (child, expected_child)) in children.iter().zip(&expected.children).enumerate() {
+        node_matches(child, expected_child, &format!("{path}/{i}"))?;
+    }
+    Ok(())
+}
+
+/// Checks that the tree, starting at the root and following the children
+/// in order, has exactly the expected structure. On mismatch, returns
+/// a description of the first difference, including the path of child
+/// indices that leads to the mismatched node.
+pub fn tree_matches(state: &TreeState, expected: &ExpectedNode) -> Result<(), String> {
+    node_matches(&state.root(), expected, "root")
+}
+
+/// Builds an [`ExpectedNode`](crate::ExpectedNode) from a role,
+/// an optional name in parentheses, and optional children in braces.
+#[macro_export]
+macro_rules! expected_node {
+    ($role:ident $(($name:expr))? $({
+        $($child_role:ident $(($child_name:expr))? $({ $($grandchildren:tt)* })?),* $(,)?
+    })?) => {
+        $crate::ExpectedNode::new($crate::__private::Role::$role)
+            $(.name($name))?
+            .children(::std::vec![$($(
+                $crate::expected_node!(
+                    $child_role $(($child_name))? $({ $($grandchildren)* })?
+                )
+            ),*)?])
+    };
+}
+
+/// Asserts that a [`TreeState`] has the given structure, written as
+/// nested roles, each with an optional name in parentheses and optional
+/// children in braces. Every child must be listed.
+///
+/// ```
+/// # use accesskit::{NodeId, Role};
+/// # use accesskit_consumer::Tree;
+/// # use accesskit_test_utils::{assert_tree_matches, TreeBuilder};
+/// let update = TreeBuilder::new(NodeId(0), Role::Window)
+///     .child(NodeId(0), NodeId(1), Role::Button, |b| b.set_name("OK"))
+///     .child(NodeId(0), NodeId(2), Role::GenericContainer, |_| ())
+///     .build();
+/// let tree = Tree::new(update, false);
+/// assert_tree_matches!(tree.state(), Window {
+///     Button("OK"),
+///     GenericContainer,
+/// });
+/// ```
+#[macro_export]
+macro_rules! assert_tree_matches {
+    ($state:expr, $($expected:tt)+) => {
+        if let Err(message) = $crate::tree_matches($state, &$crate::expected_node!($($expected)+)) {
+            panic!("tree doesn't match the expected structure: {}", message);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{NodeId, Role};
+    use accesskit_consumer::Tree;
+
+    use super::{tree_matches, ExpectedNode};
+    use crate::TreeBuilder;
+
+    fn test_tree() -> Tree {
+        let update = TreeBuilder::new(NodeId(0), Role::Window)
+            .child(NodeId(0), NodeId(1), Role::GenericContainer, |_| ())
+            .child(NodeId(1), NodeId(2), Role::Button, |b| b.set_name("OK"))
+            .child(NodeId(0), NodeId(3), Role::Label, |b| b.set_name("Status"))
+            .build();
+        Tree::new(update, false)
+    }
+
+    #[test]
+    fn macro_builds_expected_nodes() {
+        assert_eq!(
+            ExpectedNode::new(Role::Window).children([
+                ExpectedNode::new(Role::GenericContainer)
+                    .children([ExpectedNode::new(Role::Button).name("OK")]),
+                ExpectedNode::new(Role::Label),
+            ]),
+            crate::expected_node!(Window {
+                GenericContainer { Button("OK") },
+                Label,
+            })
+        );
+    }
+
+    #[test]
+    fn matching_tree() {
+        let tree = test_tree();
+        crate::assert_tree_matches!(tree.state(), Window {
+            GenericContainer {
+                Button("OK"),
+            },
+            Label("Status"),
+        });
+    }
+
+    #[test]
+    fn mismatches() {
+        let tree = test_tree();
+        assert_eq!(
+            Err("root/0/0: expected name \"Cancel\", found Some(\"OK\")".into()),
+            tree_matches(
+                tree.state(),
+                &crate::expected_node!(Window {
+                    GenericContainer { Button("Cancel") },
+                    Label,
+                })
+            )
+        );
+        assert_eq!(
+            Err("root/1: expected role Button, found Label".into()),
+            tree_matches(
+                tree.state(),
+                &crate::expected_node!(Window {
+                    GenericContainer { Button },
+                    Button,
+                })
+            )
+        );
+        assert_eq!(
+            Err("root: expected 1 children, found 2".into()),
+            tree_matches(
+                tree.state(),
+                &crate::expected_node!(Window { GenericContainer })
+            )
+        );
+    }
+}
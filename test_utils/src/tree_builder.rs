@@ -0,0 +1,118 @@
+// Copyright 2024 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{NodeBuilder, NodeId, Role, TreeUpdate, TreeUpdateBuilder};
+
+/// A fluent API for building a full [`TreeUpdate`] in tests.
+///
+/// Children are added to their parent in the order in which they are
+/// added to the builder.
+///
+/// ```
+/// # use accesskit::{NodeId, Role};
+/// # use accesskit_test_utils::TreeBuilder;
+/// let update = TreeBuilder::new(NodeId(0), Role::Window)
+///     .child(NodeId(0), NodeId(1), Role::Button, |b| b.set_name("OK"))
+///     .focus(NodeId(1))
+///     .build();
+/// assert_eq!(2, update.nodes.len());
+/// ```
+pub struct TreeBuilder {
+    nodes: Vec<(NodeId, NodeBuilder)>,
+    focus: NodeId,
+}
+
+impl TreeBuilder {
+    /// Starts a tree with the given root. The root is initially focused.
+    pub fn new(root_id: NodeId, role: Role) -> Self {
+        Self {
+            nodes: vec![(root_id, NodeBuilder::new(role))],
+            focus: root_id,
+        }
+    }
+
+    fn builder_mut(&mut self, id: NodeId) -> &mut NodeBuilder {
+        let Some((_, builder)) = self.nodes.iter_mut().find(|(node_id, _)| *node_id == id) else {
+            panic!("Node {id:?} hasn't been added to the tree builder");
+        };
+        builder
+    }
+
+    /// Adds a node as the last child of `parent_id`, which must already
+    /// have been added, and calls `configure` to set its other properties.
+    pub fn child(
+        mut self,
+        parent_id: NodeId,
+        id: NodeId,
+        role: Role,
+        configure: impl FnOnce(&mut NodeBuilder),
+    ) -> Self {
+        assert!(
+            self.nodes.iter().all(|(node_id, _)| *node_id != id),
+            "Node {id:?} was added to the tree builder twice"
+        );
+        self.builder_mut(parent_id).push_child(id);
+        let mut builder = NodeBuilder::new(role);
+        configure(&mut builder);
+        self.nodes.push((id, builder));
+        self
+    }
+
+    /// Calls `configure` on a node that has already been added,
+    /// such as the root.
+    pub fn configure(mut self, id: NodeId, configure: impl FnOnce(&mut NodeBuilder)) -> Self {
+        configure(self.builder_mut(id));
+        self
+    }
+
+    pub fn focus(mut self, id: NodeId) -> Self {
+        self.focus = id;
+        self
+    }
+
+    pub fn build(self) -> TreeUpdate {
+        let root_id = self.nodes[0].0;
+        TreeUpdateBuilder::new()
+            .tree_root(root_id)
+            .nodes(
+                self.nodes
+                    .into_iter()
+                    .map(|(id, builder)| (id, builder.build())),
+            )
+            .focus(self.focus)
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{NodeId, Role};
+
+    use super::TreeBuilder;
+
+    #[test]
+    fn children_in_order() {
+        let update = TreeBuilder::new(NodeId(0), Role::Window)
+            .child(NodeId(0), NodeId(1), Role::GenericContainer, |_| ())
+            .child(NodeId(1), NodeId(2), Role::Button, |b| b.set_name("OK"))
+            .child(NodeId(0), NodeId(3), Role::Label, |_| ())
+            .configure(NodeId(0), |b| b.set_name("Window"))
+            .focus(NodeId(2))
+            .build();
+        assert_eq!(NodeId(0), update.tree.unwrap().root);
+        assert_eq!(NodeId(2), update.focus);
+        let (_, root) = &update.nodes[0];
+        assert_eq!(Some("Window"), root.name());
+        assert_eq!(&[NodeId(1), NodeId(3)], root.children());
+        let (_, container) = &update.nodes[1];
+        assert_eq!(&[NodeId(2)], container.children());
+    }
+
+    #[test]
+    #[should_panic(expected = "hasn't been added")]
+    fn unknown_parent() {
+        TreeBuilder::new(NodeId(0), Role::Window).child(NodeId(1), NodeId(2), Role::Button, |_| ());
+    }
+}